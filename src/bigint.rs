@@ -0,0 +1,298 @@
+use std::cmp::Ordering;
+use std::fmt;
+
+// sign and magnitude, the magnitude in base 2^32 with the least significant limb first.
+// the magnitude never ends in a zero limb, and zero is never negative
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BigInt {
+  neg: bool,
+  mag: Vec<u32>
+}
+
+fn trim(mut mag: Vec<u32>) -> Vec<u32> {
+  while mag.last() == Some(&0) {
+    mag.pop();
+  }
+  mag
+}
+
+fn cmp_mag(l: &[u32], r: &[u32]) -> Ordering {
+  l.len().cmp(&r.len()).then_with(|| l.iter().rev().cmp(r.iter().rev()))
+}
+
+fn add_mag(l: &[u32], r: &[u32]) -> Vec<u32> {
+  let mut out = Vec::with_capacity(l.len().max(r.len()) + 1);
+  let mut carry = 0u64;
+  for i in 0..l.len().max(r.len()) {
+    let sum = *l.get(i).unwrap_or(&0) as u64 + *r.get(i).unwrap_or(&0) as u64 + carry;
+    out.push(sum as u32);
+    carry = sum >> 32;
+  }
+  out.push(carry as u32);
+  trim(out)
+}
+
+// l must be at least r
+fn sub_mag(l: &[u32], r: &[u32]) -> Vec<u32> {
+  let mut out = Vec::with_capacity(l.len());
+  let mut borrow = 0i64;
+  for (i, a) in l.iter().enumerate() {
+    let mut diff = *a as i64 - *r.get(i).unwrap_or(&0) as i64 - borrow;
+    borrow = (diff < 0) as i64;
+    if diff < 0 { diff += 1 << 32; }
+    out.push(diff as u32);
+  }
+  trim(out)
+}
+
+fn mul_mag(l: &[u32], r: &[u32]) -> Vec<u32> {
+  let mut out = vec![0u32; l.len() + r.len()];
+  for (i, a) in l.iter().enumerate() {
+    let mut carry = 0u64;
+    for (j, b) in r.iter().enumerate() {
+      let cur = out[i + j] as u64 + *a as u64 * *b as u64 + carry;
+      out[i + j] = cur as u32;
+      carry = cur >> 32;
+    }
+    out[i + r.len()] = carry as u32;
+  }
+  trim(out)
+}
+
+fn divrem_small(l: &[u32], r: u32) -> (Vec<u32>, u32) {
+  let mut quot = vec![0u32; l.len()];
+  let mut rem = 0u64;
+  for i in (0..l.len()).rev() {
+    let cur = rem << 32 | l[i] as u64;
+    quot[i] = (cur / r as u64) as u32;
+    rem = cur % r as u64;
+  }
+  (trim(quot), rem as u32)
+}
+
+// long division a bit at a time; r must not be zero
+fn divrem_mag(l: &[u32], r: &[u32]) -> (Vec<u32>, Vec<u32>) {
+  let mut quot = vec![0u32; l.len()];
+  let mut rem: Vec<u32> = Vec::new();
+  for bit in (0..l.len() * 32).rev() {
+    // rem = rem * 2 + the next bit of l
+    let mut carry = (l[bit / 32] >> (bit % 32)) & 1;
+    for limb in rem.iter_mut() {
+      let top = *limb >> 31;
+      *limb = (*limb << 1) | carry;
+      carry = top;
+    }
+    if carry != 0 { rem.push(carry); }
+
+    if cmp_mag(&rem, r) != Ordering::Less {
+      rem = sub_mag(&rem, r);
+      quot[bit / 32] |= 1 << (bit % 32);
+    }
+  }
+  (trim(quot), rem)
+}
+
+impl BigInt {
+  fn new(neg: bool, mag: Vec<u32>) -> BigInt {
+    let mag = trim(mag);
+    BigInt { neg: neg && !mag.is_empty(), mag }
+  }
+
+  pub fn from_i64(n: i64) -> BigInt {
+    let m = n.unsigned_abs();
+    BigInt::new(n < 0, Vec::from([m as u32, (m >> 32) as u32]))
+  }
+
+  pub fn to_i64(&self) -> Option<i64> {
+    let m = self.to_u64()?;
+    if self.neg {
+      if m <= i64::MAX as u64 + 1 { Some((m as i64).wrapping_neg()) } else { None }
+    } else {
+      i64::try_from(m).ok()
+    }
+  }
+
+  // the magnitude, when it fits
+  pub fn to_u64(&self) -> Option<u64> {
+    match self.mag.len() {
+      0 => Some(0),
+      1 => Some(self.mag[0] as u64),
+      2 => Some(self.mag[0] as u64 | (self.mag[1] as u64) << 32),
+      _ => None
+    }
+  }
+
+  pub fn to_f64(&self) -> f64 {
+    let m = self.mag.iter().rev().fold(0.0, |acc, limb| acc * 4294967296.0 + *limb as f64);
+    if self.neg { -m } else { m }
+  }
+
+  pub fn is_zero(&self) -> bool {
+    self.mag.is_empty()
+  }
+
+  // an optional sign and at least one decimal digit
+  pub fn parse(s: &str) -> Option<BigInt> {
+    let (neg, digits) = match s.strip_prefix('-') {
+      Some(d) => (true, d),
+      None => (false, s.strip_prefix('+').unwrap_or(s))
+    };
+    if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+      return None;
+    }
+
+    let mut mag = Vec::new();
+    for chunk in digits.as_bytes().chunks(9) {
+      let scale = 10u32.pow(chunk.len() as u32);
+      let value = chunk.iter().fold(0u32, |acc, b| acc * 10 + (b - b'0') as u32);
+      mag = add_mag(&mul_mag(&mag, &[scale]), &[value]);
+    }
+    Some(BigInt::new(neg, mag))
+  }
+
+  pub fn neg(&self) -> BigInt {
+    BigInt::new(!self.neg, self.mag.clone())
+  }
+
+  pub fn add(&self, r: &BigInt) -> BigInt {
+    if self.neg == r.neg {
+      return BigInt::new(self.neg, add_mag(&self.mag, &r.mag));
+    }
+    match cmp_mag(&self.mag, &r.mag) {
+      Ordering::Less => BigInt::new(r.neg, sub_mag(&r.mag, &self.mag)),
+      _ => BigInt::new(self.neg, sub_mag(&self.mag, &r.mag))
+    }
+  }
+
+  pub fn sub(&self, r: &BigInt) -> BigInt {
+    self.add(&r.neg())
+  }
+
+  pub fn mul(&self, r: &BigInt) -> BigInt {
+    BigInt::new(self.neg != r.neg, mul_mag(&self.mag, &r.mag))
+  }
+
+  // truncating, as with i64; None when dividing by zero
+  pub fn divrem(&self, r: &BigInt) -> Option<(BigInt, BigInt)> {
+    if r.is_zero() {
+      return None;
+    }
+    let (q, m) = divrem_mag(&self.mag, &r.mag);
+    Some((BigInt::new(self.neg != r.neg, q), BigInt::new(self.neg, m)))
+  }
+}
+
+impl Ord for BigInt {
+  fn cmp(&self, r: &BigInt) -> Ordering {
+    match (self.neg, r.neg) {
+      (false, true) => Ordering::Greater,
+      (true, false) => Ordering::Less,
+      (false, false) => cmp_mag(&self.mag, &r.mag),
+      (true, true) => cmp_mag(&r.mag, &self.mag)
+    }
+  }
+}
+
+impl PartialOrd for BigInt {
+  fn partial_cmp(&self, r: &BigInt) -> Option<Ordering> {
+    Some(self.cmp(r))
+  }
+}
+
+impl fmt::Display for BigInt {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    if self.mag.is_empty() {
+      return write!(f, "0");
+    }
+
+    // nine digits at a time, least significant first
+    let mut chunks = Vec::new();
+    let mut mag = self.mag.clone();
+    while !mag.is_empty() {
+      let (q, r) = divrem_small(&mag, 1_000_000_000);
+      chunks.push(r);
+      mag = q;
+    }
+
+    let mut out = if self.neg { "-".to_string() } else { String::new() };
+    out += &chunks.pop().unwrap().to_string();
+    for c in chunks.iter().rev() {
+      out += &format!("{:09}", c);
+    }
+    write!(f, "{}", out)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn big(s: &str) -> BigInt {
+    BigInt::parse(s).unwrap()
+  }
+
+  #[test]
+  fn parse_and_print() {
+    for s in ["0", "7", "-7", "4294967296", "-18446744073709551616", "1000000000", "123456789012345678901234567890"] {
+      assert_eq!(big(s).to_string(), s);
+    }
+    assert_eq!(big("+12").to_string(), "12");
+    assert_eq!(big("-0").to_string(), "0");
+    assert!(!big("-0").neg);
+    assert_eq!(big("000123").to_string(), "123");
+    assert_eq!(BigInt::parse(""), None);
+    assert_eq!(BigInt::parse("-"), None);
+    assert_eq!(BigInt::parse("1.5"), None);
+    assert_eq!(BigInt::parse("1e3"), None);
+  }
+
+  #[test]
+  fn add_and_sub_carry_across_limbs() {
+    assert_eq!(big("4294967295").add(&big("1")).to_string(), "4294967296");
+    assert_eq!(big("18446744073709551615").add(&big("1")).to_string(), "18446744073709551616");
+    assert_eq!(big("18446744073709551616").sub(&big("1")).to_string(), "18446744073709551615");
+    assert_eq!(big("5").sub(&big("8")).to_string(), "-3");
+    assert_eq!(big("-5").add(&big("8")).to_string(), "3");
+    assert_eq!(big("-5").sub(&big("8")).to_string(), "-13");
+    assert_eq!(big("8").add(&big("-8")).to_string(), "0");
+    assert!(!big("8").add(&big("-8")).neg);
+  }
+
+  #[test]
+  fn mul_signs_and_carries() {
+    assert_eq!(big("4294967295").mul(&big("4294967295")).to_string(), "18446744065119617025");
+    assert_eq!(big("-123456789012345678901").mul(&big("1000")).to_string(), "-123456789012345678901000");
+    assert_eq!(big("-3").mul(&big("-4")).to_string(), "12");
+    assert_eq!(big("-3").mul(&big("0")).to_string(), "0");
+  }
+
+  #[test]
+  fn divrem_truncates() {
+    let (q, r) = big("123456789012345678901234567890").divrem(&big("1000000007")).unwrap();
+    assert_eq!(q.mul(&big("1000000007")).add(&r).to_string(), "123456789012345678901234567890");
+    assert!(r < big("1000000007"));
+    let (q, r) = big("-7").divrem(&big("2")).unwrap();
+    assert_eq!((q.to_string(), r.to_string()), ("-3".to_string(), "-1".to_string()));
+    let (q, r) = big("7").divrem(&big("-2")).unwrap();
+    assert_eq!((q.to_string(), r.to_string()), ("-3".to_string(), "1".to_string()));
+    assert_eq!(big("7").divrem(&big("0")), None);
+  }
+
+  #[test]
+  fn compare_by_sign_then_magnitude() {
+    assert!(big("-18446744073709551616") < big("-1"));
+    assert!(big("-1") < big("0"));
+    assert!(big("4294967295") < big("4294967296"));
+    assert!(big("-4294967296") < big("-4294967295"));
+    assert_eq!(big("-0").cmp(&big("0")), Ordering::Equal);
+  }
+
+  #[test]
+  fn conversions_at_the_edges() {
+    assert_eq!(BigInt::from_i64(i64::MIN).to_string(), "-9223372036854775808");
+    assert_eq!(big("-9223372036854775808").to_i64(), Some(i64::MIN));
+    assert_eq!(big("9223372036854775808").to_i64(), None);
+    assert_eq!(big("-18446744073709551615").to_u64(), Some(u64::MAX));
+    assert_eq!(big("18446744073709551616").to_u64(), None);
+  }
+}
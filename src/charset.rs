@@ -234,30 +234,57 @@ pub fn refcard() -> String {
 
 pub fn detailedinfo<S: AsRef<str>>(i: S) -> String {
   match i.as_ref() {
-    "builtins" => format!("\n  pipe      Perform pipe redirection.\n  collect   Collect stdout, stderr, and exit code.\n  num       Convert between string and numeric representations.\n  exit      Exit.\n  list      Convert between bash-style list format and data structures.\n  csv       Convert between CSV and data structures.\n  json      Convert between JSON and data structures."),
-    "list" => format!("Convert between bash-style lists and data structures.\n\nImport:\nWith a string argument, list parses the string into a corresponding data structure.\n\n     list \"1\\t2\\n3\"\n  => ┌2──────┐\n     │┌1────┐│\n     ││╭\"──╮││\n     │││ 1 │││\n     ││╰───╯││\n     ││╭\"──╮││\n     │││ 2 │││\n     ││╰───╯││\n     │└─────┘│\n     │╭\"──╮  │\n     ││ 3 │  │\n     │╰───╯  │\n     └───────┘\n\nExport:\nWith a list argument, list converts the array (max depth 2) to a corresponding serialized form.\n\n     list (1 2) 3\n  => ╭\"─────╮\n     │ 1  2 │\n     │ 3    │\n     ╰──────╯"),
-    "csv" => format!("Convert between CSV format and data structures.\n\nImport:\nWith a string argument, CSV parses the string into a corresponding data structure.\n\n     csv \"1\\n2,3\"\n  => ┌2──────┐\n     │┌1────┐│\n     ││╭\"──╮││\n     │││ 1 │││\n     ││╰───╯││\n     │└─────┘│\n     │┌1────┐│\n     ││╭\"──╮││\n     │││ 2 │││\n     ││╰───╯││\n     ││╭\"──╮││\n     │││ 3 │││\n     ││╰───╯││\n     │└─────┘│\n     └───────┘\n\nExport:\nWith a list argument, csv converts the array (max depth 2) to a corresponding serialized form.\n\n     csv (1 2) (3 4) 5\n  => ╭\"────╮\n     │ 1,2 │\n     │ 3,4 │\n     │ 5   │\n     │     │\n     ╰─────╯\n\nA custom separator can be specified as the left argument in either mode."),
-    "json" => format!("Convert between JSON format and data structures.\n\nImport:\nWith a string argument, JSON parses the string into a corresponding data structure.  Objects are converted to an array of keys before an array of values.\n\n     json \"{{\\\"foo\\\":2, \\\"bar\\\":4}}\"\n  => ┌2────────┐\n     │┌1──────┐│\n     ││╭\"────╮││\n     │││ foo │││\n     ││╰─────╯││\n     ││╭\"────╮││\n     │││ bar │││\n     ││╰─────╯││\n     │└───────┘│\n     │┌1──┐    │\n     ││2 4│    │\n     │└───┘    │\n     └─────────┘\n\nExport:\nWith a list argument, json converts the array to a corresponding serialized form.\n\n     json 1 2 3\n  => ╭\"────────╮\n     │ [1,2,3] │\n     ╰─────────╯"),
-    "exit" => format!("Exit.  Optionally takes an integral right argument to specify the exit code."),
-    "pipe" => format!("Redirect a process' output.  Has no effect on non-shell functions.\n\npipe takes a process as its left argument and a list of symbols on its right.\nSymbols are processed by paired in twos, with each first symbol being the \"from\" of the redirect and each second symbol being the \"to\" of the redirect.  Available symbols are:\n\n  -o    STDOUT\n  -e    STDERR\n  -n    NULL (/dev/null)\n\nIn addition, there are two macro symbols.\n\n  --swap    Equivalent to -oe -eo.\n  --null    Equivalent to -on -en.\n\nExample usage:\n     cat \"missing.file\" | pipe --swap | sed -u \"s/.*/{{&}}/\" | pipe --swap\n  => {{cat: missing.file: No such file or directory}}"),
-    "collect" => format!("Collect a process' stdout, exit code, and stderr (in this order) in an array.  Returns an unchanged value for non-shell functions.\n\n     cat \"missing.file\" | collect\n  => ┌1──────────────────────────────────────────────────────┐\n     │╭\"─╮ 1 ╭\"─────────────────────────────────────────────╮│\n     ││  │   │ cat: missing.file: No such file or directory ││\n     │╰──╯   │                                              ││\n     │       ╰──────────────────────────────────────────────╯│\n     └───────────────────────────────────────────────────────┘"),
-    "num" => format!("Convert between string and number.\n\n    num \"5\"\n  => 5\n\n     num 5\n  => ╭\"──╮\n     │ 5 │\n     ╰───╯"),
+    "builtins" => "\n  pipe      Perform pipe redirection.\n  collect   Collect stdout, stderr, and exit code.\n  num       Convert between string and numeric representations.\n  exit      Exit.\n  list      Convert between bash-style list format and data structures.\n  csv       Convert between CSV and data structures.\n  json      Convert between JSON and data structures.\n  bg        Run a process in the background, or resume a job.\n  fg        Wait on a job in the foreground.\n  wait      Wait for jobs to finish.\n  jobs      List background jobs.\n  try       Turn a failure into a value.\n  if        Choose between two branches, evaluating only one.\n  else      Separate the branches of an if.\n  getenv    Read an environment variable, or list them all.\n  export    Set an environment variable.\n  unset     Remove an environment variable.\n  withenv   Set environment variables for one process.\n  glob      List the paths matching a pattern.\n  cd        Change directory.\n  pushd     Change directory, saving the current one.\n  popd      Return to the last directory saved.\n  dirs      List the directory stack.\n  dict      Make a dict from keys and values.\n  keys      List the keys of a dict.\n  values    List the values of a dict.\n  table     Make a table of rows under named columns.\n  files     List a directory as a table.".to_string(),
+    "list" => "Convert between bash-style lists and data structures.\n\nImport:\nWith a string argument, list parses the string into a corresponding data structure.\n\n     list \"1\\t2\\n3\"\n  => ┌2──────┐\n     │┌1────┐│\n     ││╭\"──╮││\n     │││ 1 │││\n     ││╰───╯││\n     ││╭\"──╮││\n     │││ 2 │││\n     ││╰───╯││\n     │└─────┘│\n     │╭\"──╮  │\n     ││ 3 │  │\n     │╰───╯  │\n     └───────┘\n\nExport:\nWith a list argument, list converts the array (max depth 2) to a corresponding serialized form.\n\n     list (1 2) 3\n  => ╭\"─────╮\n     │ 1  2 │\n     │ 3    │\n     ╰──────╯\n\nGiven --header on its left, list reads the first line as the names of the columns of a table.  A table is written out with that line first.  Its fields become numbers as with csv.\n\n     --header list (cat \"procs.tsv\")".to_string(),
+    "csv" => "Convert between CSV format and data structures.\n\nImport:\nWith a string argument, CSV parses the string into a table, its first line naming the columns.  A short line is padded with null, and fields past the names get columns named by their position.  A field which is a number written plainly, as 30 or -2.5, becomes a number; anything else, such as 030 or 1e3, stays a string, so it is written out again as it was read.\n\n     csv \"name,size\\nsrc,4096\\nb.txt,30\"\n  => ┌table───────┐\n     │name  │ size│\n     │──────┼─────│\n     │src   │ 4096│\n     │b.txt │   30│\n     └────────────┘\n\nExport:\nWith a list argument, csv converts the array (max depth 2) to a corresponding serialized form.  A table is written with its header first.\n\n     csv (1 2) (3 4) 5\n  => ╭\"────╮\n     │ 1,2 │\n     │ 3,4 │\n     │ 5   │\n     │     │\n     ╰─────╯\n\nA custom separator can be specified as the left argument in either mode.".to_string(),
+    "json" => "Convert between JSON format and data structures.\n\nImport:\nWith a string argument, JSON parses the string into a corresponding data structure.  Objects become dicts, keeping the order of their keys.  null, true, and false become values of their own: null and false are falsy, like 0, but equal only to themselves.  Integers are read exactly however long they are, while numbers with a fraction or an exponent, as in `2.0` or `1e3`, become floats.\n\n     json \"{\\\"foo\\\":2, \\\"bar\\\":4}\"\n  => ┌dict─┐\n     │foo 2│\n     │bar 4│\n     └─────┘\n\nExport:\nWith an array or dict argument, json converts it to a corresponding serialized form, so an object read in is written back out unchanged.  Integers are written in full, and floats always with a point or an exponent, as in `100.0`, so each is read back as it was; a float which is infinite or not a number becomes null.\n\n     json 1 2 3\n  => ╭\"────────╮\n     │ [1,2,3] │\n     ╰─────────╯".to_string(),
+    "exit" => "Exit.  Optionally takes an integral right argument to specify the exit code.\n\nAt the prompt, Ctrl-D on an empty line also exits.  Ctrl-C only clears the line, or interrupts the running process; a process ended this way returns 130.".to_string(),
+    "pipe" => "Redirect a process' output.  Has no effect on non-shell functions.\n\npipe takes a process as its left argument and a list of symbols on its right.\nSymbols are processed by paired in twos, with each first symbol being the \"from\" of the redirect and each second symbol being the \"to\" of the redirect.  Available symbols are:\n\n  -o    STDOUT\n  -e    STDERR\n  -n    NULL (/dev/null)\n\nA string in place of the \"to\" symbol names a file, which is truncated before writing; prefix it with -a to append instead.  The \"from\" symbol -i reads a process' input from the named file.\n\n     ls | pipe -o \"out.log\" -ea \"err.log\"\n     sort | pipe -i \"input.txt\"\n\nIn addition, there are two macro symbols.\n\n  --swap    Equivalent to -oe -eo.\n  --null    Equivalent to -on -en.\n\nExample usage:\n     cat \"missing.file\" | pipe --swap | sed -u \"s/.*/{&}/\" | pipe --swap\n  => {cat: missing.file: No such file or directory}".to_string(),
+    "collect" => "Collect a process' stdout, exit code, stderr, and the exit code of every stage (in this order) in an array.  Returns an unchanged value for non-shell functions.\n\n     cat \"missing.file\" | collect\n  => ┌2───────────────────────────────────────────────┐\n     │╭\"─╮                                            │\n     ││  │                                            │\n     │╰──╯                                            │\n     │1                                               │\n     │╭\"─────────────────────────────────────────────╮│\n     ││ cat: missing.file: No such file or directory ││\n     ││                                              ││\n     │╰──────────────────────────────────────────────╯│\n     │┌1┐                                             │\n     ││1│                                             │\n     │└─┘                                             │\n     └────────────────────────────────────────────────┘\n\nThe exit code is the last stage's, as for a lone process; the final array lists every stage's code, upstream first.\n\n     sh \"-c\" \"exit 2\" | cat | collect\n  => ┌2────┐\n     │╭\"─╮ │\n     ││  │ │\n     │╰──╯ │\n     │0    │\n     │╭\"─╮ │\n     ││  │ │\n     │╰──╯ │\n     │┌1──┐│\n     ││2 0││\n     │└───┘│\n     └─────┘".to_string(),
+    "bg" => "Run a process in the background.  The process' output still goes to the terminal, but its input is empty unless piped or redirected.  Returns a job, which is reported on completion before the next prompt.\n\n     j; make | bg\n  => [JOB 1]\n\nWith a job (or nothing, for the latest job), resume it if it was stopped.".to_string(),
+    "fg" => "Bring a job (or the latest job, if none is given) to the foreground, resuming it if stopped and waiting for it to finish.  The job holds the terminal meanwhile, so Ctrl-C interrupts it, and Ctrl-Z stops it and returns the job for a later fg or bg.  Returns the job's exit code.\n\n     sleep 5 | bg\n     fg\n  => 0".to_string(),
+    "wait" => "Wait for a job to finish and return its exit code.  With no argument, wait for every job and return an array of exit codes.  A stopped job would never finish, so waiting on it is an error, and wait with no argument leaves it out; continue it with fg or bg first.\n\n     j; sh \"-c\" \"exit 3\" | bg\n     wait j\n  => 3".to_string(),
+    "jobs" => "List the job table.  Each row holds the job, its state (running, stopped, or done), and the line which started it.  Finished jobs stay in the table until collected with `wait` or `fg`.".to_string(),
+    "fn" => "Write a function with named arguments.  fn is followed by up to two names and a parenthesized body.  With two names, the first is bound to the left argument and the second to the right; with one, it is bound to the right argument; with none, the arguments are named x and y.\n\n     sq; fn n (n * n)\n     sq 7\n  => 49\n\n     2 fn (x * y) 5\n  => 10\n\nAn argument shadows any name or command of the same name within the body.\n\nA body spread over several lines holds one statement per line, and gives the value of the last.  Names assigned in it are local to the call, shadowing those outside; see `outer` to write to an enclosing scope instead.\n\nA function sees the names of the scopes it was written in, not those of its caller, and keeps them after the call which made it has returned.\n\n     adder; fn a (fn b (a + b))\n     add5; adder 5\n     add5 1\n  => 6\n\nA function may call itself by the name it is assigned to, as names within a body are looked up only when it runs.  Calls may nest up to 1000 deep, or as many as RECURSION_LIMIT is assigned.\n\n     fact; fn n ((n < 1) if (1 else (n * (fact (n - 1)))))\n     fact 5\n  => 120\n\n     mean; fn v (\n       total; +/ v\n       total % (# v)\n     )\n     mean 2 4 9\n  => 5".to_string(),
+    "dict" => format!("Make a dict, which maps string keys to values in the order the keys were first given, from pairs of keys and values.\n\n     dict (\"name\" \"brie\" \"size\" 3)\n  => ┌dict─────────┐\n     │name ╭\"─────╮│\n     │     │ brie ││\n     │     ╰──────╯│\n     │size 3       │\n     └─────────────┘\n\nAn array of keys beside an array of values works too, and a dict is returned unchanged.  A key given twice keeps its first place and its last value.\n\nA dict may also be written in braces, each key a name or a string, and each value an atom as in an array; parenthesize anything longer.  {{:}} is the empty dict.  A dict is shown this way within a table, and by )info.\n\n     {{name: \"brie\", \"size in g\": (100 * 3)}}\n  => ┌dict──────────────┐\n     │name      ╭\"─────╮│\n     │          │ brie ││\n     │          ╰──────╯│\n     │size in g 300     │\n     └──────────────────┘\n\nIndex a dict with a key, which must be there.  Joining two dicts with {0} merges them, the right one's values winning and its new keys going last.  Each walks over a dict's values, keeping the keys:\n\n     d{1}{{x: 1, y: 2}}\n     \"y\" {2} d\n  => 2\n     json (10 +{3}(0 1){1} d)\n  => ╭\"────────────────╮\n     │ {{\"x\":11,\"y\":12}} │\n     ╰─────────────────╯\n\nTwo dicts are equal when they hold the same keys with equal values, in any order.  See also keys, values, and json.", CHARSET::Concat, CHARSET::Assign, CHARSET::Index, CHARSET::Each),
+    "keys" => "List the keys of a dict, in order, or the columns of a table.\n\n     keys (dict (\"name\" \"brie\" \"size\" 3))\n  => ┌1───────┐\n     │╭\"─────╮│\n     ││ name ││\n     │╰──────╯│\n     │╭\"─────╮│\n     ││ size ││\n     │╰──────╯│\n     └────────┘".to_string(),
+    "values" => "List the values of a dict, in the order of its keys, or the rows of a table as dicts.\n\n     values (dict (\"x\" 1 \"y\" 2))\n  => ┌1──┐\n     │1 2│\n     └───┘".to_string(),
+    "STATUS" => "The exit status of the last line's processes.  A line which runs no process gives 0.  In a pipeline the rightmost stage which failed decides, so a failure upstream is not hidden by a later stage which succeeded.  PIPESTATUS holds every stage's status, upstream first.  Both are read-only, and are read before the line which mentions them runs anything.\n\n     true | false | true\n     PIPESTATUS\n  => ┌1────┐\n     │0 1 0│\n     └─────┘\n\nA non-zero status is shown before the prompt until the next line runs.  A failure handled by try, or tested as the condition of if, leaves the status at 0.".to_string(),
+    "outer" => "Write a name in an enclosing scope rather than the current one.  Names are looked up through the call's own scope, then the scopes the function was written in, out to the script and the shell; a plain assignment binds in the innermost.  outer assigns where the name is already bound, or else in the shell's scope.\n\n     count; 0\n     tick; fn n (\n       outer count; count + n\n       count\n     )\n     tick 5\n  => 5".to_string(),
+    "try" => "Evaluate an expression, turning a failure into an error value instead of stopping the line.  A process run under try fails when it exits unsuccessfully, and otherwise gives its exit code as it would alone.  Its output goes to the terminal as it runs either way, and its stderr is kept for the error.\n\n     try (num \"abc\")\n  => ┌1─────────────────────────────────────────┐\n     │╭\"──────╮                                 │\n     ││ value │                                 │\n     │╰───────╯                                 │\n     │╭\"───────────────────────────────────────╮│\n     ││ Invalid candidate for numeric parsing. ││\n     │╰────────────────────────────────────────╯│\n     │1 6                                       │\n     └──────────────────────────────────────────┘\n\nThe error value holds the kind of error (syntax, name, type, value, index, io, process, job, recursion, or internal), its message, and its line and column.\n\nWith a left argument, give that instead; if it is a function, call it with the error value.  Either way it is used only on failure, wherever the try is nested.\n\n     \"none\" try (cat \"missing.txt\")\n  => ╭\"─────╮\n     │ none │\n     ╰──────╯".to_string(),
+    "if" => "Choose between two branches.  The left argument is the condition, and the right is the branch taken when it holds, then else and the branch taken when it does not, parenthesized together.  Only the chosen branch is evaluated, so a process in the other is never run.\n\n     n; 5\n     (n > 3) if (\"big\" else \"small\")\n  => ╭\"────╮\n     │ big │\n     ╰─────╯\n\nA number holds when it is not zero; a process holds when it exits successfully.  Parenthesize each branch which is more than a single value.  Without else, the whole right argument is the one branch, and an empty array is given when the condition does not hold.  An else branch may itself be an if, for a chain of conditions.\n\n     (test -f \"build.log\") if ((cat \"build.log\") else (echo \"no log\"))\n     (n > 9) if (\"huge\" else ((n > 3) if (\"big\" else \"small\")))".to_string(),
+    "else" => "Separate the branches of an if: the one taken when the condition holds on its left, and the one taken when it does not on its right.  Both are left unevaluated until the if chooses between them, and else means nothing on its own.\n\n     0 if (\"yes\" else \"no\")\n  => ╭\"───╮\n     │ no │\n     ╰────╯".to_string(),
+    "getenv" => "Read an environment variable.  An unset variable gives an empty array.\n\n     getenv \"HOME\"\n  => ╭\"───────────╮\n     │ /home/brie │\n     ╰────────────╯\n\nWith no argument, give every variable as a pair of its name and value, sorted by name.".to_string(),
+    "export" => "Set an environment variable, which processes started afterward inherit.  The name is on the left and the value, a string or number, on the right.\n\n     \"EDITOR\" export \"vi\"\n\nWith only a name, export the brie name of the same name.\n\n     PAGER; \"less\"\n     export \"PAGER\"".to_string(),
+    "unset" => "Remove an environment variable.\n\n     unset \"EDITOR\"".to_string(),
+    "withenv" => "Set environment variables for a single process, leaving the shell's own untouched.  Takes a process on its left and pairs of names and values on its right.\n\n     make | withenv \"CC\" \"clang\" \"CFLAGS\" \"-O2\"".to_string(),
+    "table" => format!("Make a table, which holds rows under named columns, from an array of dicts as rows, an array of arrays headed by the column names, or a dict of columns of the same length.  A key missing from a row is null there.  csv gives a table, as does list given --header.\n\n     t{0}csv \"name,size\\nsrc,4096\\nb.txt,30\"\n\nIndex a table with a column name for that column, with a row number for that row as a dict, or with several of either for a smaller table.  Take gives its first or last rows.  Grading a column gives the order to index the rows by, comparing numbers as numbers:\n\n     o{0}{1} (\"size\" {2} t)\n     o {2} t\n  => ┌table───────┐\n     │name  │ size│\n     │──────┼─────│\n     │b.txt │   30│\n     │src   │ 4096│\n     └────────────┘\n\nWhere and Each go over the rows as dicts, and when Each gives a dict for every row, those make a table again:\n\n     w{0}(fn r ((\"size\" {2} r) {3} 100)){4} t\n     w {2} t\n\nJoining two tables with {5} gives the rows of both under the columns of either.  A table too wide for the terminal has its widest columns cut short, and one too long has its last rows left off.  See also keys, values, csv, list, and json.", CHARSET::Assign, CHARSET::Greater, CHARSET::Index, CHARSET::Less, CHARSET::Where, CHARSET::Concat),
+    "files" => format!("List a directory as a table, one row per entry in sorted order, without running ls.  With no argument, the current directory is listed; a file gives a row of its own.\n\n  name          The name, or the path within the directory when recursing.\n  type          file, dir, symlink, fifo, socket, block, or char.\n  size          In bytes.\n  modified      Local time to the minute, as 2024-05-01 09:30.\n  permissions   As ls shows them, such as rwxr-xr-x.\n  target        Where a symlink points, or null.\n\nA symlink is listed as itself, and not followed when recursing.  On its left, files takes -a to include hidden files and -r to list the contents of each directory after it.\n\n     -ar files \"src\"\n\nThe table may go straight into Where, sorting, and Each, which see each row as a dict:\n\n     t{0}files ()\n     o{0}{1} (\"size\" {2} t)\n     o {2} t\n     big{0}(fn r ((\"size\" {2} r) {1} 1000)){3} t", CHARSET::Assign, CHARSET::Less, CHARSET::Index, CHARSET::Where),
+    "glob" => "List the paths matching a pattern, in sorted order.\n\n  *       Any run of characters within a name.\n  ?       Any one character.\n  [...]   Any one of the characters listed, or in a range such as a-z; [!...] any other.\n  **      Any number of directories, or as the last part, everything beneath.\n  {a,b}   Either alternative, each matched in turn.\n\nWildcards do not match a leading dot; write the dot to match hidden files.  A backslash makes the next character literal.\n\n     glob \"src/*.{rs,toml}\"\n     ls -l (glob \"**/*.md\")\n\nArrays of patterns give all their matches together, and arrays given to a process spread into separate arguments.  A pattern matching nothing gives nothing, unless glob is given --strict on its left, which makes it an error.\n\n     --strict glob \"*.log\"".to_string(),
+    "cd" => "Change directory.  With no argument, go home; with \"-\", go back to the previous directory, which is kept in $OLDPWD.\n\n     cd \"~/src\"\n     cd \"-\"\n  => ╭\"───────────╮\n     │ /home/brie │\n     ╰────────────╯\n\nA leading ~ stands for the home directory, and ~name for that user's, here and in the strings given to processes, redirections, glob, and files.  Write \\~ for a ~ which is meant as itself, as in `echo \"\\~ is home\"`.".to_string(),
+    "pushd" => "Change directory, saving the current one on the directory stack.  Returns the stack, as dirs does.\n\n     pushd \"/tmp\"\n     popd".to_string(),
+    "popd" => "Return to the directory most recently saved by pushd, removing it from the stack.  Returns what remains, as dirs does.".to_string(),
+    "dirs" => "List the current directory, then the directory stack from the most recently saved.".to_string(),
+    "num" => "Convert between string and number.\n\n    num \"5\"\n  => 5\n\n     num 5\n  => ╭\"──╮\n     │ 5 │\n     ╰───╯\n\nWhole numbers are exact at any size, growing past 64 bits rather than overflowing; anything with a point or an exponent, like 2.5 or 1e6, is a 64-bit float.  Arithmetic on whole numbers stays exact, and division gives a whole number when it divides evenly.  Adding a float to either side gives a float.  A whole number and a float of the same value are equal.\n\n     num (9223372036854775807 + 1)\n  => ╭\"────────────────────╮\n     │ 9223372036854775808 │\n     ╰─────────────────────╯\n\njson keeps whole numbers exact up to 20 digits, and refuses to write larger ones; csv and list keep every digit.".to_string(),
     
-    "(" => format!("Open parenthesis.  (Really?)"),
-    ")" => format!("Close parenthesis.  When found at the start of a line, indicates a shell command.  Available shell commands are:\n\n  \x1b[0;1m)help [command?]\x1b[0;32m\n  \x1b[0;1m)info [expression]\x1b[0;32m\n  \x1b[0;1m)wipe\x1b[0;32m\n  \x1b[0;1m)clear\x1b[0;32m\n  \x1b[0;1m)rtf [filename]\x1b[0;32m"),
+    "(" => "Open parenthesis.  (Really?)\n\nA statement continues onto the next line while a parenthesis is left open, or when a line ends in a pipe, which would otherwise have nothing to pipe into.  In the repl, continuation lines are prompted with `>`; CTRL-C abandons the whole statement.\n\n     s; (1 2 3\n       + 10)\n     t; ls |\n       sort".to_string(),
+    ")" => "Close parenthesis.  When found at the start of a line, indicates a shell command.  Available shell commands are:\n\n  \x1b[0;1m)help [command?]\x1b[0;32m\n  \x1b[0;1m)info [expression]\x1b[0;32m\n  \x1b[0;1m)wipe\x1b[0;32m\n  \x1b[0;1m)clear\x1b[0;32m\n  \x1b[0;1m)rtf [filename]\x1b[0;32m\n  \x1b[0;1m)source [filename?]\x1b[0;32m".to_string(),
+    "history" => "Line history is kept in ~/.brie_history, up to the last 1000 distinct lines, and reloaded when the shell starts.\n\nKeymap:\n\n  - UP/DOWN ARROW: Step through history.\n  - CTRL-R: Search backward through history; type to narrow the search and press again for older matches.\n  - CTRL-S: Search forward from the current match.\n  - ENTER: Run the matched line.  Any other key leaves the search to edit it.".to_string(),
+    "completion" => "Press TAB to complete the word before the cursor.  Inside a string, file paths are completed; at the start of a `)` line, shell commands are; anywhere else, builtins, assigned names, and executables on $PATH.\n\nWhen several candidates match, the common prefix is filled in and the candidates are listed below the line.  Pressing TAB again cycles through them.".to_string(),
     ")help" => format!("Get help for a command.  (You're doing it.)"),
     "repl" => format!("Available repl commands are:\n\n 
- )info\n  )wipe\n  )clear\n  )rtf\n  )help\n  )cm\n  )c"),
+ )info\n  )wipe\n  )clear\n  )rtf\n  )help\n  )cm\n  )c\n  )source"),
     ")cm" => format!("Swap commit mode between manual and automatic.  Automatic commit mode adds every valid command to the `)rtf` history buffer.  Manual commit mode only adds commands prefixed with `)c` or commands where the next line is a `)c` invocation."),
     ")c" => format!("Commit to the `)rtf` buffer.  Either commit this line if it is not empty past `)c`, or commit the previous line."),
     ")info" => format!(")info [expression] displays a tree version of a program line.  This indicates only the parsed form of the line, not the value resulting from executing the line.\n\nTrees are constructed according to the following rules:\n\n  - A fork in the tree represents function application.\n\n    f\n    ├──┐\n    l  r\n\n    The function may be enclosed in square dotted brackets if it is complex.\n\n  - Items side-by-side indicate an array of items.\n    Items under a \x1b[0;1m[]\x1b[0;32m symbol also indicate an array (in this case, nested)."),
+    ")source" => ")source [filename] evaluates a file statement by statement in the current session, stopping at the first error, which is headed with the file, line, and column it came from.  Without a filename, it reloads the startup file.\n\nThe startup file, ~/.brierc, is evaluated before the first prompt, so names assigned in it are available in every session.  Scripts skip it unless run with the --rc flag, as in `brie --rc script.brie`.  A script which stops at an error exits with status 1, reporting the error on stderr, and one stopped with Ctrl-C exits with status 130; run it with --json-errors to report the error as a JSON object (kind, message, note, file, line, col) instead, and with --errexit to stop at the first statement whose processes fail (see STATUS).".to_string(),
     ")wipe" => format!("Wipe the current recorded buffer used by )rtf.  Previous line history is still accessible, but (unless run again) will not appear in )rtf output."),
     ")clear" => format!("Clear the screen.  This does not destroy any history."),
     ")rtf" => format!("Repl to file.  )rtf [filename] opens an editor which allows the user to choose which lines in the current )rtf history buffer (by default, the repl's line history) to write to the file specified in the command invokation.\n\nKeymap for editor:\n\n  - UP/DOWN ARROW: Scroll through lines.\n  - BACKSPACE: Toggle line inclusion.\n  - ESC: Exit editor and cancel write."),
     x if x == CHARSET::Plus => format!("Arithmetic addition.\n\n     6 {0} 7\n  => 13\n\nString concatenation.\n\n     \"Hello\" {0} \", world\"\n  => ╭\"─────────────╮\n     │ Hello, world │\n     ╰──────────────╯", CHARSET::Plus),
     x if x == CHARSET::Minus => format!("Arithmetic subtraction.\n\n     6 {} 7\n  => -1\n\nConvert a string to characters.\n\n     -\"foo\"\n  => ┌1────┐\n     │╭\"──╮│\n     ││ f ││\n     │╰───╯│\n     │╭\"──╮│\n     ││ o ││\n     │╰───╯│\n     │╭\"──╮│\n     ││ o ││\n     │╰───╯│\n     └─────┘", CHARSET::Minus),
     x if x == CHARSET::Times => format!("Arithmetic multiplication.\n\n     6 {0} 7\n  => 42\n\nLogical negation.\n\n     {0} 1\n  => 0", CHARSET::Times),
-    x if x == CHARSET::Divide => format!("Arithmetic division.\n\n     6 {} 7\n  => 0.85714287", CHARSET::Divide),
+    x if x == CHARSET::Divide => format!("Arithmetic division.\n\n     6 {0} 7\n  => 0.8571428571428571\n\n     6 {0} 3\n  => 2", CHARSET::Divide),
     x if x == CHARSET::Equal => format!("Equality.\n\n     6 {} 1 6 3 2 6\n  => ┌1────────┐\n     │0 1 0 0 1│\n     └─────────┘", CHARSET::Equal),
     
     x if x == CHARSET::LTack => format!("Choose the left argument.  Left tack ignores its right argument.\n\n     3 2 {0} 5 4\n  => ┌1──┐\n     │3 2│\n     └───┘\n\nGet the first element of an array.\n\n     {0} 1 2 3 4\n  => 1", CHARSET::LTack),
@@ -271,13 +298,13 @@ pub fn detailedinfo<S: AsRef<str>>(i: S) -> String {
 
     x if x == CHARSET::Assign => format!("Evaluate an expression and assign it to a valid name.\n\n     a{0}2 {1} 4\n     a {1} 3\n  => 9\n\nAssign an expression to a name without evaluating it.\n\n     b{0}{0}echo -n \"Eval\"\n     (b) {1} \"uated\"\n  => ╭\"──────────╮\n     │ Evaluated │\n     ╰───────────╯\n\nNote the parenthetical enclosure of the variable.  This is critical to force application within the value, as the variable might otherwise be treated as a partially-applied function.", CHARSET::Assign, CHARSET::Plus),
 
-    x if x == CHARSET::Pipe => format!("Fix precedence of a left-side value.\n\n     2 {1} 3 {0} {1} 4\n  => 9", CHARSET::Pipe, CHARSET::Plus),
+    x if x == CHARSET::Pipe => format!("Fix precedence of a left-side value.\n\n     2 {1} 3 {0} {1} 4\n  => 9\n\nA value piped into a process is written to its stdin.  Strings are written verbatim; numbers and arrays are written in `list` format, one line per element with nested arrays as tab-separated fields.  Anything nested deeper is written as JSON.\n\n     \"b\" \"c\" \"a\" {0} sort\n  => a\n     b\n     c", CHARSET::Pipe, CHARSET::Plus),
     x if x == CHARSET::AntiPipe => format!("Fix precedence of a right-side value.\n\n     4 {1} {0} 3 {1} 2 \n  => 9", CHARSET::AntiPipe, CHARSET::Plus),
 
     x if x == CHARSET::Iota => format!("Produce a sequence of consecutive integers.\n\n     {0} 4\n  => ┌1──────┐\n     │0 1 2 3│\n     └───────┘\n\nSplit on zero.\n\n     1 1 0 1 0 1 {0} 1 2 3 4 5 6\n  => ┌2────────────┐\n     │┌1──┐ ┌1┐ ┌1┐│\n     ││1 2│ │4│ │6││\n     │└───┘ └─┘ └─┘│\n     └─────────────┘", CHARSET::Iota),
     x if x == CHARSET::Where => format!("Find indices of occurances where a predicate returns true.\n     ({1}3){0} 1 4 3 1 3 3 5\n  => ┌1────┐\n     │2 4 5│\n     └─────┘", CHARSET::Where, CHARSET::Equal),
 
-    x if x == CHARSET::Greater => format!("Comparison.\n\n     5 {0} 2\n  => 0\n\nFind indices that would sort an array in ascending order.\n\n     {0} 1 4 3 0\n  => ┌1──────┐\n     │3 0 2 1│\n     └───────┘", CHARSET::Greater),
+    x if x == CHARSET::Greater => format!("Comparison.\n\n     5 {0} 2\n  => 0\n\nFind indices that would sort an array in ascending order.\n\n     {0} 1 4 3 0\n  => ┌1──────┐\n     │3 0 2 1│\n     └───────┘\n\nStrings compare without regard to case, and arrays element by element.  Values of different kinds sort by kind: null first, then bools, numbers, strings, symbols, arrays, dicts, and tables.\n\n     {0} json \"[\\\"b\\\", 2, null, true]\"\n  => ┌1──────┐\n     │2 3 1 0│\n     └───────┘", CHARSET::Greater),
     x if x == CHARSET::Less => format!("Comparison.\n\n     5 {0} 2\n  => 1\n\nFind indices that would sort an array in descending order.\n\n     {} 1 4 3 0\n  => ┌1──────┐\n     │1 2 0 3│\n     └───────┘", CHARSET::Less),
 
     x if x == CHARSET::MaxLast => format!("Maximum (OR).\n\n     5 {0} 2\n  => 5\n\nChoose last element.\n\n     {0} 1 4 3 2\n  => 2", CHARSET::MaxLast),
@@ -296,7 +323,7 @@ pub fn detailedinfo<S: AsRef<str>>(i: S) -> String {
     x if x == CHARSET::Rotate => format!("Reverse an array.\n\n     . 1 2 3\n  => ┌1────┐\n     │3 2 1│\n     └─────┘\n\nRotate an array.  Positive means leftward movement, negative means rightward movement.\n\n     2 . 1 2 3 4 5\n  => ┌1────────┐\n     │3 4 5 1 2│\n     └─────────┘"),
     
 
-    x if x == "language" => format!("\x1b[0;1;4mBrie Shell Language Tutorial\x1b[0;32m\n\nThe Brie Shell is an interactive language.  Each line is an expression which evaluates to a result.  The result of an expression's evaluation in this tutorial will be shown on the following line after =>.  In the REPL, it is simply shown on the following line.\n\nStandard arithmetic operators apply.  Note however that division is represented by %.\n\n     1 + 2\n  => 3\n\n     6 % 3\n  => 2\n\n     \"Hello, \" + \"world!\"\n  => ╭\"──────────────╮\n     │ Hello, world! │\n     ╰───────────────╯\n\nBrie Shell is an array language.  Arrays are written without notation simply by juxtaposition.  The REPL displays arrays within boxes.  The number at the top indicates the \"depth\" of the array, i.e. how many arrays can be found nested inside it.\n\n     1 2 3 4\n  => ┌1──────┐\n     │1 2 3 4│\n     └───────┘\n\nBrie Shell is an array language.  Functions are \"depth-polymorphic\" in that they may be applied to arrays all at once.  This applies even if the arrays are nested, which can be written using parentheses.\n\n     (1 2) (3 4) + (5 6) (7 8)\n  => ┌2──────┐\n     │┌1──┐  │\n     ││6 8│  │\n     │└───┘  │\n     │┌1────┐│\n     ││10 12││\n     │└─────┘│\n     └───────┘\n\nIt's possible to apply a function \"between\" elements of an array using the Reduce modifier, `/`.  This is called Reduce because it collapses an array using the function.\n\n     +/ 1 2 3 4\n  => 10\n\nNotice that the modifier is placed after the function, `+`.\nThere is another modifier which performs a similar function, Scan.\n\n     +\\ 1 2 3\n  => ┌1────┐\n     │1 3 6│\n     └─────┘\n\nNote that Scan returns an array as if reduce had been applied to just the first element of array, then the first and second element, then the first and second and third, and so on.\n\nIn addition to functions on single objects, we can manipulate entire arrays.  Concat, `,`, joins two arrays together.  Grade Up and Grade Down, `<` and `<`, respectively, returns a list of indices that, if the elements put in that order, would sort the array.  Such functions will not be described in detail here, as they can be learned through careful use of the refcard and the )help command.\n\nIn some situations, one may wish to apply an array-oriented function to each inner array rather than an array as a whole, or a scalar-oriented function to an entire array, and so on.  This can be achieved through usage of the modifier Depth, `$`.  Depth takes an argument which specifies the depth \"downward\" to traverse in the array, starting at zero.  A negative number specifies how \"high\" up from the bottom to go to traverse the array, starting at zero.\n\n     < (3 2) (1 7)\n  => ┌1──┐\n     │0 1│\n     └───┘\n     || by the way, this is a comment\n     || not intended behavior — sorts the entire array\n\n     <$1; (3 2) (1 7)\n  => ┌2────┐\n     │┌1──┐│\n     ││1 0││\n     │└───┘│\n     │┌1──┐│\n     ││0 1││\n     │└───┘│\n     └─────┘\n\nHere we see the syntax for the usage of a modifier which takes a value — the value is placed after the modifier and followed by a semicolon.  Any value, including an array, is allowed here.  Additionally, a function may be used, which is evaluated with the left and right arguments of the whole expression to yield a result.  For example, the following invocation evaluates at depth 1 because the result of evaluating Shape `#` on the right argument (there is no left argument) is 1.\n\n     -$#; (,1 2)\n  => ┌2──────┐\n     │┌1────┐│\n     ││-1 -2││\n     │└─────┘│\n     └───────┘\n\n(Note that this of course is irrelevant, as `-` already applies at scalar [maximum] depth.)\n\nIt is useful to make more complicated functions out of existing functions, as in building blocks.  This is done through the formation of tacit trains.  Tacit trains follow two rules:\n  1. Multiple functions in a row are applied in succession.\n    5 -+ 2 is equivalent to -(5 + 2)\n  2. If there are 3 or more functions in a train, the outer two form a \"fork\".  Each of the outer two is applied to the arguments, then the middle is applied between the two results.\n    5 -+* 2 is equivalent to (5 - 2) + (5 * 2)\n\nTrains are critical to forming any useful experession.  For example, we can write the greater-than-or-equal-to operator as >^= simply with the definitions of Greater `>`, Or `^`, and Equals `=`.  Or we could even write not-greater-than-or-equal-to as >*^= (noting * to be unary not) — but this is of course simply the less-than operator.\n\nThe final aspect of creating functional forms is partial application.  Suppose we wish to find the indices of an array where the values are greater than five.  We can use the Where `?` modifier to do this, but that requires creating a function that returns true for values greater than five.  Doing so involves \"binding\" the function `>` to the right-side value `5`.\n\nIn the Brie shell, binding a function to a value in this way (partial application) looks little different than applying a function to a value.  In fact, the following example works just as expected:\n\n     (> 5)? 1 2 6 3 10\n\n  => ┌1──┐\n     │2 4│\n     └───┘\n\nIt is however generally bad practice to write functions in this way because it is not guaranteed that they are to work.  There are cases in which it is impossible to disambiguate whether the call is intended to produce a value or a partially-applied function, such as in the expression `(> 5) }} 1 2 3`.  Brie uses semantic whitespace to disambiguate such instances — functions written without surrounding whitespace will be treated as partial application, while functions with whitespace will be treated as standard function calls.  Thus, the above example should be written `(>5)? 1 2 6 3 10` so as to cause no confusion (and the previous example written `(>5)}} 1 2 3`).\n\nIt is often useful to give names to expressions or patterns that are used again.  This can be done through the assignment formation, `;`.\n\n     a_name;15\n     a_name + 27\n  => 42\n\n     Note that `;` evaluates the expression before binding it to a name, making it impossible to assign a name to a functional form using `;`.  For that, the lazy binding form may be used, `;;`.  `;;` does not evaluate the expression before assigning it to a name.\n\n     a_fn;; -+\n     1 a_fn 2\n  => -3\n\n\x1b[4mUsing Brie as a Shell\x1b[0;32m\n\nAs a true shell, any identifier found in $PATH will be executed as a shell command.  To faciliate command usage, members of the symbol datatype may be constructed as in bash.  `-` may be followed by any number of single characters to form a list of symbols, while `--` may be followed by characters to form a single multi-character symbol.  Note that in a symbol list, the symbols will be assimilated into the enclosing array, facilitating constructions such as `-ab -c` evaluating to `-a -b -c`.\n\n     ls -aS --color\n  => [output of ls with all files, sorted by size, and in color]\n\nBrie defines a pipe operator as in bash.  However, the pipe operator is not a special form; it is merely syntactical sugar for grouping application on the left.  \n\n     echo \"foo\" | cat\n  => foo\n\nNote that commands receive their STDIN as a left argument and their ARGS as a right argument.\n\nJust as the pipe operator groups leftward, the antipipe operator groups rightward.\n\n     cat [ echo \"some.file\"\n  => [contents of some.file]\n\nA final note on pipe: as pipe and antipipe are not special forms, they work equally well on non-shell functions as they do on shell functions.\n\nBrie defines the `collect` and `redirect` primitives for manipulating output from shell commands.  Info for these can be found in the )help docs.\n\n\x1b[4mUsing the REPL\x1b[0;32m\n\nThe Brie repl itself has certain commands which can be used to affect the operation of the REPL.  Details for each can be found by invoking `)help repl`.\n\nShell commands begin with `)` and are followed by a word.  `)help` itself is a shell command.\n\nThe Brie shell keeps a running history of valid, executed commands.  This history can be written to a file by using `)rtf`, allowing one to construct a shell script simply by interacting with the REPL in real time.  `)rtf` also provides an editor to remove unwanted lines.\nWhile `)rtf` by default includes every executed line in its history, this can be changed to remove unnecessary clutter.  By invoking `)cm`, the \"commit mode\" is switched between automatic and manual.  Automatic (default) mode commits every valid line, while manual mode requires a line to be prefixed with `)c` or followed by a single line of `)c` to be added to the history session.\n\n)wipe can be used to empty the history buffer.\n\n_____ . . . _____"),
+    x if x == "language" => "\x1b[0;1;4mBrie Shell Language Tutorial\x1b[0;32m\n\nThe Brie Shell is an interactive language.  Each line is an expression which evaluates to a result.  The result of an expression's evaluation in this tutorial will be shown on the following line after =>.  In the REPL, it is simply shown on the following line.\n\nStandard arithmetic operators apply.  Note however that division is represented by %.\n\n     1 + 2\n  => 3\n\n     6 % 3\n  => 2\n\n     \"Hello, \" + \"world!\"\n  => ╭\"──────────────╮\n     │ Hello, world! │\n     ╰───────────────╯\n\nBrie Shell is an array language.  Arrays are written without notation simply by juxtaposition.  The REPL displays arrays within boxes.  The number at the top indicates the \"depth\" of the array, i.e. how many arrays can be found nested inside it.\n\n     1 2 3 4\n  => ┌1──────┐\n     │1 2 3 4│\n     └───────┘\n\nBrie Shell is an array language.  Functions are \"depth-polymorphic\" in that they may be applied to arrays all at once.  This applies even if the arrays are nested, which can be written using parentheses.\n\n     (1 2) (3 4) + (5 6) (7 8)\n  => ┌2──────┐\n     │┌1──┐  │\n     ││6 8│  │\n     │└───┘  │\n     │┌1────┐│\n     ││10 12││\n     │└─────┘│\n     └───────┘\n\nIt's possible to apply a function \"between\" elements of an array using the Reduce modifier, `/`.  This is called Reduce because it collapses an array using the function.\n\n     +/ 1 2 3 4\n  => 10\n\nNotice that the modifier is placed after the function, `+`.\nThere is another modifier which performs a similar function, Scan.\n\n     +\\ 1 2 3\n  => ┌1────┐\n     │1 3 6│\n     └─────┘\n\nNote that Scan returns an array as if reduce had been applied to just the first element of array, then the first and second element, then the first and second and third, and so on.\n\nIn addition to functions on single objects, we can manipulate entire arrays.  Concat, `,`, joins two arrays together.  Grade Up and Grade Down, `<` and `<`, respectively, returns a list of indices that, if the elements put in that order, would sort the array.  Such functions will not be described in detail here, as they can be learned through careful use of the refcard and the )help command.\n\nIn some situations, one may wish to apply an array-oriented function to each inner array rather than an array as a whole, or a scalar-oriented function to an entire array, and so on.  This can be achieved through usage of the modifier Depth, `$`.  Depth takes an argument which specifies the depth \"downward\" to traverse in the array, starting at zero.  A negative number specifies how \"high\" up from the bottom to go to traverse the array, starting at zero.\n\n     < (3 2) (1 7)\n  => ┌1──┐\n     │0 1│\n     └───┘\n     || by the way, this is a comment\n     || not intended behavior — sorts the entire array\n\n     <$1; (3 2) (1 7)\n  => ┌2────┐\n     │┌1──┐│\n     ││1 0││\n     │└───┘│\n     │┌1──┐│\n     ││0 1││\n     │└───┘│\n     └─────┘\n\nHere we see the syntax for the usage of a modifier which takes a value — the value is placed after the modifier and followed by a semicolon.  Any value, including an array, is allowed here.  Additionally, a function may be used, which is evaluated with the left and right arguments of the whole expression to yield a result.  For example, the following invocation evaluates at depth 1 because the result of evaluating Shape `#` on the right argument (there is no left argument) is 1.\n\n     -$#; (,1 2)\n  => ┌2──────┐\n     │┌1────┐│\n     ││-1 -2││\n     │└─────┘│\n     └───────┘\n\n(Note that this of course is irrelevant, as `-` already applies at scalar [maximum] depth.)\n\nIt is useful to make more complicated functions out of existing functions, as in building blocks.  This is done through the formation of tacit trains.  Tacit trains follow two rules:\n  1. Multiple functions in a row are applied in succession.\n    5 -+ 2 is equivalent to -(5 + 2)\n  2. If there are 3 or more functions in a train, the outer two form a \"fork\".  Each of the outer two is applied to the arguments, then the middle is applied between the two results.\n    5 -+* 2 is equivalent to (5 - 2) + (5 * 2)\n\nTrains are critical to forming any useful experession.  For example, we can write the greater-than-or-equal-to operator as >^= simply with the definitions of Greater `>`, Or `^`, and Equals `=`.  Or we could even write not-greater-than-or-equal-to as >*^= (noting * to be unary not) — but this is of course simply the less-than operator.\n\nThe final aspect of creating functional forms is partial application.  Suppose we wish to find the indices of an array where the values are greater than five.  We can use the Where `?` modifier to do this, but that requires creating a function that returns true for values greater than five.  Doing so involves \"binding\" the function `>` to the right-side value `5`.\n\nIn the Brie shell, binding a function to a value in this way (partial application) looks little different than applying a function to a value.  In fact, the following example works just as expected:\n\n     (> 5)? 1 2 6 3 10\n\n  => ┌1──┐\n     │2 4│\n     └───┘\n\nIt is however generally bad practice to write functions in this way because it is not guaranteed that they are to work.  There are cases in which it is impossible to disambiguate whether the call is intended to produce a value or a partially-applied function, such as in the expression `(> 5) } 1 2 3`.  Brie uses semantic whitespace to disambiguate such instances — functions written without surrounding whitespace will be treated as partial application, while functions with whitespace will be treated as standard function calls.  Thus, the above example should be written `(>5)? 1 2 6 3 10` so as to cause no confusion (and the previous example written `(>5)} 1 2 3`).\n\nIt is often useful to give names to expressions or patterns that are used again.  This can be done through the assignment formation, `;`.\n\n     a_name;15\n     a_name + 27\n  => 42\n\n     Note that `;` evaluates the expression before binding it to a name, making it impossible to assign a name to a functional form using `;`.  For that, the lazy binding form may be used, `;;`.  `;;` does not evaluate the expression before assigning it to a name.\n\n     a_fn;; -+\n     1 a_fn 2\n  => -3\n\nWhen a train grows unreadable, a function may instead be written with named arguments using `fn`, up to two names, and a parenthesized body.  With two names, the first is the left argument and the second the right; with one, it is the right argument; with none, they are `x` and `y`.  A function written this way is a value, so `;` may name it.\n\n     hyp; fn a b ((a * a) + (b * b))\n     3 hyp 4\n  => 25\n\nA body over several lines may assign names of its own, which are forgotten when the function returns.  `outer` writes to a name outside instead.\n\n\x1b[4mUsing Brie as a Shell\x1b[0;32m\n\nAs a true shell, any identifier found in $PATH will be executed as a shell command.  To faciliate command usage, members of the symbol datatype may be constructed as in bash.  `-` may be followed by any number of single characters to form a list of symbols, while `--` may be followed by characters to form a single multi-character symbol.  Note that in a symbol list, the symbols will be assimilated into the enclosing array, facilitating constructions such as `-ab -c` evaluating to `-a -b -c`.\n\n     ls -aS --color\n  => [output of ls with all files, sorted by size, and in color]\n\nBrie defines a pipe operator as in bash.  However, the pipe operator is not a special form; it is merely syntactical sugar for grouping application on the left.  \n\n     echo \"foo\" | cat\n  => foo\n\nNote that commands receive their STDIN as a left argument and their ARGS as a right argument.\n\nJust as the pipe operator groups leftward, the antipipe operator groups rightward.\n\n     cat [ echo \"some.file\"\n  => [contents of some.file]\n\nA final note on pipe: as pipe and antipipe are not special forms, they work equally well on non-shell functions as they do on shell functions.\n\nBrie defines the `collect` and `redirect` primitives for manipulating output from shell commands.  Info for these can be found in the )help docs.\n\n\x1b[4mUsing the REPL\x1b[0;32m\n\nThe Brie repl itself has certain commands which can be used to affect the operation of the REPL.  Details for each can be found by invoking `)help repl`.\n\nShell commands begin with `)` and are followed by a word.  `)help` itself is a shell command.\n\nThe Brie shell keeps a running history of valid, executed commands.  This history can be written to a file by using `)rtf`, allowing one to construct a shell script simply by interacting with the REPL in real time.  `)rtf` also provides an editor to remove unwanted lines.\nWhile `)rtf` by default includes every executed line in its history, this can be changed to remove unnecessary clutter.  By invoking `)cm`, the \"commit mode\" is switched between automatic and manual.  Automatic (default) mode commits every valid line, while manual mode requires a line to be prefixed with `)c` or followed by a single line of `)c` to be added to the history session.\n\n)wipe can be used to empty the history buffer.\n\n_____ . . . _____".to_string(),
     
     _ => format!("\x1b[1;31mUnknown item.\x1b[0;32m")
   }
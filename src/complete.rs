@@ -0,0 +1,113 @@
+use crate::scope::Env;
+use crate::token::BUILTINS;
+
+use std::env;
+use std::os::unix::fs::PermissionsExt;
+use std::path::Path;
+
+pub struct Completion {
+  pub start: usize, // in chars; the word runs from here to the cursor
+  pub candidates: Vec<String>,
+  pub quoted: bool
+}
+
+fn in_string(before: &str) -> Option<usize> {
+  let mut open = None;
+  let mut escaped = false;
+  for (i, c) in before.chars().enumerate() {
+    if escaped {
+      escaped = false;
+    } else if open.is_some() && c == '\\' {
+      escaped = true;
+    } else if c == '"' {
+      open = if open.is_some() { None } else { Some(i + 1) };
+    }
+  }
+  open
+}
+
+fn paths(word: &str) -> Vec<String> {
+  let (dir, file) = match word.rfind('/') {
+    Some(i) => (&word[..i + 1], &word[i + 1..]),
+    None => ("", word)
+  };
+
+  let entries = match std::fs::read_dir(if dir.is_empty() { Path::new(".") } else { Path::new(dir) }) {
+    Ok(es) => es,
+    Err(_) => { return Vec::new(); }
+  };
+
+  entries
+    .filter_map(|e| e.ok())
+    .filter_map(|e| {
+      let name = e.file_name().into_string().ok()?;
+      if !name.starts_with(file) || (name.starts_with('.') && !file.starts_with('.')) {
+        return None;
+      }
+      let slash = if e.path().is_dir() { "/" } else { "" };
+      Some(format!("{}{}{}", dir, name, slash))
+    })
+    .collect()
+}
+
+fn executables(word: &str) -> Vec<String> {
+  let path = match env::var_os("PATH") {
+    Some(p) => p,
+    None => { return Vec::new(); }
+  };
+
+  env::split_paths(&path)
+    .filter_map(|d| std::fs::read_dir(d).ok())
+    .flatten()
+    .filter_map(|e| e.ok())
+    .filter(|e| e.metadata().map(|m| m.is_file() && m.permissions().mode() & 0o111 != 0).unwrap_or(false))
+    .filter_map(|e| e.file_name().into_string().ok())
+    .filter(|n| n.starts_with(word))
+    .collect()
+}
+
+pub fn complete(line: &str, pos: usize, env: &Env, env_commands: &[&str]) -> Completion {
+  let before = line.chars().take(pos).collect::<String>();
+
+  if let Some(start) = in_string(&before) {
+    let word = before.chars().skip(start).collect::<String>();
+    let mut candidates = paths(&word);
+    candidates.sort();
+    return Completion { start, candidates, quoted: true };
+  }
+
+  let word = before.chars().rev().take_while(|c| c.is_alphanumeric() || *c == '_').collect::<Vec<char>>().into_iter().rev().collect::<String>();
+  let start = pos - word.chars().count();
+
+  // `)` commands only make sense as the whole line
+  if before.trim_start().starts_with(')') {
+    let is_name = before.trim_start().chars().skip(1).all(|c| c.is_alphanumeric());
+    let candidates = if is_name { env_commands.iter().filter(|c| c.starts_with(word.as_str())).map(|c| c.to_string()).collect() } else { Vec::new() };
+    return Completion { start, candidates, quoted: false };
+  }
+
+  if word.is_empty() || word.chars().next().unwrap().is_numeric() {
+    return Completion { start, candidates: Vec::new(), quoted: false };
+  }
+
+  let mut candidates = BUILTINS.iter().filter(|b| b.starts_with(word.as_str())).map(|b| b.to_string()).collect::<Vec<String>>();
+  candidates.extend(env.keys().into_iter().filter(|k| k.starts_with(word.as_str())));
+  candidates.extend(executables(&word));
+  candidates.sort();
+  candidates.dedup();
+
+  Completion { start, candidates, quoted: false }
+}
+
+pub fn common_prefix(candidates: &[String]) -> String {
+  let mut prefix = match candidates.first() {
+    Some(c) => c.clone(),
+    None => { return String::new(); }
+  };
+  for c in candidates {
+    while !c.starts_with(prefix.as_str()) {
+      prefix.pop();
+    }
+  }
+  prefix
+}
@@ -0,0 +1,83 @@
+use std::env;
+use std::ffi::{CStr, CString};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+// directories saved by pushd, the most recent last
+static STACK: Mutex<Vec<PathBuf>> = Mutex::new(Vec::new());
+
+fn stack() -> std::sync::MutexGuard<'static, Vec<PathBuf>> {
+  STACK.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+fn passwd_dir(pw: *const libc::passwd) -> Option<String> {
+  if pw.is_null() {
+    return None;
+  }
+  unsafe { CStr::from_ptr((*pw).pw_dir).to_str().ok().map(|d| d.to_string()) }
+}
+
+// $HOME, or the password database when it is unset
+pub fn home() -> Option<String> {
+  env::var("HOME").ok().filter(|h| !h.is_empty()).or_else(|| passwd_dir(unsafe { libc::getpwuid(libc::getuid()) }))
+}
+
+fn user_home(name: &str) -> Option<String> {
+  let name = CString::new(name).ok()?;
+  passwd_dir(unsafe { libc::getpwnam(name.as_ptr()) })
+}
+
+// ~ and ~user at the start of a path; an unknown user is left alone, as in other shells.
+// strings are always quoted, so \~ stands for a ~ which is not expanded
+pub fn expand_tilde(path: &str) -> String {
+  if let Some(literal) = path.strip_prefix("\\~") {
+    return format!("~{}", literal);
+  }
+  let Some(rest) = path.strip_prefix('~') else {
+    return path.to_string();
+  };
+  let (user, tail) = rest.split_at(rest.find('/').unwrap_or(rest.len()));
+  let dir = if user.is_empty() { home() } else { user_home(user) };
+  match dir {
+    Some(d) => d + tail,
+    None => path.to_string()
+  }
+}
+
+// OLDPWD and PWD follow every change, so `cd "-"` and child processes see them
+pub fn change(dir: &Path) -> io::Result<()> {
+  let old = env::current_dir().ok();
+  env::set_current_dir(dir)?;
+  if let Some(old) = old {
+    env::set_var("OLDPWD", old);
+  }
+  if let Ok(new) = env::current_dir() {
+    env::set_var("PWD", new);
+  }
+  Ok(())
+}
+
+pub fn push(dir: &Path) -> io::Result<()> {
+  let here = env::current_dir()?;
+  change(dir)?;
+  stack().push(here);
+  Ok(())
+}
+
+// None when the stack is empty; a directory which cannot be entered stays on it
+pub fn pop() -> Option<io::Result<PathBuf>> {
+  let dir = stack().last()?.clone();
+  Some(change(&dir).map(|_| {
+    stack().pop();
+    dir
+  }))
+}
+
+// the current directory, then the stack from the most recent
+pub fn list() -> Vec<String> {
+  env::current_dir().into_iter()
+    .chain(stack().iter().rev().cloned())
+    .map(|d| d.to_string_lossy().into_owned())
+    .collect()
+}
@@ -1,22 +1,177 @@
 use crate::parse::Location;
 
+use std::cell::RefCell;
 use std::fmt;
 
-pub fn location_format<S: AsRef<str>>(s: S, col: usize, len: usize) -> String {
-  format!("\x1b[35m|\x1b[0m{0}\n\r{1}\x1b[31m{2}", s.as_ref(), " ".repeat(col + 1), "^".repeat(len))
+thread_local! {
+  // file name and first line of the statement being run, while running a script
+  static SOURCE: RefCell<Option<(String, usize)>> = const { RefCell::new(None) };
 }
 
-pub fn error<S: AsRef<str>, S2: AsRef<str>, S3: AsRef<str>>(s: S, col: usize, len: usize, errmsg: S2, note: S3) -> String {
-  format!(
-    "\x1b[31mERR:\x1b[33m {0}\n\r\n\r{1}\n\r\n\r\x1b[35mNote:\x1b[33m {2}\x1b[0m", 
-    errmsg.as_ref(), 
-    location_format(s, col, len),
-    note.as_ref()
-  )
+pub fn set_source(source: Option<(String, usize)>) -> Option<(String, usize)> {
+  SOURCE.with(|s| s.replace(source))
 }
 
-pub fn internalfailure<E: fmt::Display, S: AsRef<str>, S2: AsRef<str>>(e: E, action: S, s: S2, l: &Location) -> String {
-  error(s.as_ref(), l.col, l.len, format!("[INTERNAL] Failed to {}.", action.as_ref()), noteformat!("Trace:\n\r{}", e))
+fn current_source() -> Option<(String, usize)> {
+  SOURCE.with(|s| s.borrow().clone())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ErrorKind {
+  Syntax,   // the line could not be tokenized or parsed
+  Name,     // unknown identifier
+  Type,     // a value of the wrong sort was given
+  Value,    // right sort, but unusable: unparseable strings, mismatched lengths
+  Index,    // out of bounds
+  Io,       // files, directories, spawning processes
+  Process,  // a process exited unsuccessfully, which is only an error under `try`
+  Job,      // the job table
+  Recursion, // functions nested past RECURSION_LIMIT
+  Interrupt, // a script stopped by Ctrl-C
+  Internal,
+  Preview   // a side effect refused while typing; never shown as an error
+}
+
+impl ErrorKind {
+  pub fn name(&self) -> &'static str {
+    match self {
+      ErrorKind::Syntax => "syntax",
+      ErrorKind::Name => "name",
+      ErrorKind::Type => "type",
+      ErrorKind::Value => "value",
+      ErrorKind::Index => "index",
+      ErrorKind::Io => "io",
+      ErrorKind::Process => "process",
+      ErrorKind::Job => "job",
+      ErrorKind::Recursion => "recursion",
+      ErrorKind::Interrupt => "interrupt",
+      ErrorKind::Internal => "internal",
+      ErrorKind::Preview => "preview"
+    }
+  }
+}
+
+// where an error happened: the statement, the place within it, and the script it came from
+#[derive(Debug, Clone)]
+pub struct Span {
+  pub source: String,
+  pub location: Location,
+  pub file: Option<(String, usize)>
+}
+
+#[derive(Debug, Clone)]
+pub struct Error {
+  pub kind: ErrorKind,
+  pub message: String,
+  pub note: String,
+  pub span: Option<Box<Span>>, // boxed to keep Results small
+  pub cause: Option<Box<Error>>
+}
+
+impl Error {
+  pub fn new<S: AsRef<str>, S2: AsRef<str>>(kind: ErrorKind, message: S, note: S2) -> Error {
+    Error { kind, message: message.as_ref().to_string(), note: note.as_ref().to_string(), span: None, cause: None }
+  }
+
+  pub fn caused_by(mut self, cause: Error) -> Error {
+    self.cause = Some(Box::new(cause));
+    self
+  }
+
+  pub fn render(&self, color: bool) -> String {
+    let mut out = String::new();
+    if let Some(Span { file: Some((ref name, line)), location, .. }) = self.span.as_deref() {
+      out += &format!("\x1b[35m{}:{}:{}:\x1b[0m ", name, line + location.line + 1, location.col + 1);
+    }
+    out += &format!("\x1b[31mERR:\x1b[33m {}", self.message);
+    if let Some(ref span) = self.span {
+      out += &format!("\n\r\n\r{}", format_span(&span.source, span.location, span.file.as_ref().map(|f| f.1)));
+    }
+    if !self.note.is_empty() {
+      out += &format!("\n\r\n\r\x1b[35mNote:\x1b[33m {}", self.note);
+    }
+    out += "\x1b[0m";
+    if let Some(ref cause) = self.cause {
+      out += &format!("\n\r\x1b[35mCaused by:\x1b[0m\n\r{}", cause.render(color));
+    }
+
+    if color { out } else { strip_color(&out).replace("\n\r", "\n") }
+  }
+
+  pub fn to_json(&self) -> json::JsonValue {
+    let mut obj = json::JsonValue::new_object();
+    obj["kind"] = self.kind.name().into();
+    obj["message"] = strip_color(&self.message).into();
+    obj["note"] = strip_color(&self.note).replace("\n\r", "\n").into();
+    if let Some(ref span) = self.span {
+      let line = span.file.as_ref().map(|f| f.1).unwrap_or(0) + span.location.line;
+      obj["file"] = span.file.as_ref().map(|f| f.0.clone()).into();
+      obj["line"] = (line + 1).into();
+      obj["col"] = (span.location.col + 1).into();
+      obj["len"] = span.location.len.into();
+      obj["source"] = span.source.split('\n').nth(span.location.line).unwrap_or("").into();
+    }
+    if let Some(ref cause) = self.cause {
+      obj["cause"] = cause.to_json();
+    }
+    obj
+  }
+}
+
+impl fmt::Display for Error {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "{}", self.render(true))
+  }
+}
+
+pub fn strip_color(s: &str) -> String {
+  let mut out = String::new();
+  let mut chars = s.chars();
+  while let Some(c) = chars.next() {
+    if c == '\x1b' {
+      // CSI sequences run up to a letter
+      for c in chars.by_ref() {
+        if c.is_ascii_alphabetic() { break; }
+      }
+    } else {
+      out.push(c);
+    }
+  }
+  out
+}
+
+// only the offending line of a multi-line statement is shown, numbered so it can be found in the script
+fn format_span(s: &str, l: Location, base: Option<usize>) -> String {
+  let line = s.split('\n').nth(l.line).unwrap_or("");
+  let number = match base {
+    Some(line) => format!("{}", line + l.line + 1),
+    None if s.contains('\n') => format!("{}", l.line + 1),
+    None => String::new()
+  };
+  format!("\x1b[35m{0}|\x1b[0m{1}\n\r{2}\x1b[31m{3}", number, line, " ".repeat(number.len() + l.col + 1), "^".repeat(l.len))
+}
+
+pub fn location_format<S: AsRef<str>>(s: S, l: Location) -> String {
+  format_span(s.as_ref(), l, current_source().map(|f| f.1))
+}
+
+pub fn error<S: AsRef<str>, S2: AsRef<str>, S3: AsRef<str>>(kind: ErrorKind, s: S, l: Location, errmsg: S2, note: S3) -> Error {
+  Error {
+    kind,
+    message: errmsg.as_ref().to_string(),
+    note: note.as_ref().to_string(),
+    span: Some(Box::new(Span { source: s.as_ref().to_string(), location: l, file: current_source() })),
+    cause: None
+  }
+}
+
+pub fn internalfailure<E: fmt::Display, S: AsRef<str>, S2: AsRef<str>>(e: E, action: S, s: S2, l: &Location) -> Error {
+  error(ErrorKind::Internal, s.as_ref(), *l, format!("[INTERNAL] Failed to {}.", action.as_ref()), noteformat!("Trace:\n\r{}", e))
+}
+
+// builtins with side effects refuse to run during the live preview
+pub fn preview() -> Error {
+  Error::new(ErrorKind::Preview, "[cmd]", "")
 }
 
 // maintain yellow but not on values, values stay white
@@ -26,4 +181,4 @@ macro_rules! noteformat {
     format!($fmt_str, $(format!("\x1b[0m{}\x1b[33m", $args)),*)
   }};
 }
-pub(crate) use noteformat;
\ No newline at end of file
+pub(crate) use noteformat;
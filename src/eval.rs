@@ -1,23 +1,79 @@
-use crate::error::{error, noteformat, internalfailure};
+use crate::error::{self, error, noteformat, internalfailure, preview, Error, ErrorKind};
 use crate::charset::CHARSET;
-use crate::parse::{AST, ASTVal, Location, ToRedirect, Redirect, is_fn, fill_from_right, is_lazy, fathometer, respect_fill};
+use crate::parse::{AST, ASTVal, Location, ToRedirect, Redirect, is_fn, fill_from_right, is_lazy, is_try, fathometer, respect_fill};
 use crate::token;
-
+use crate::jobs;
+use crate::number::Number;
+use crate::dirs;
+use crate::files;
+use crate::glob;
+use crate::scope::Env;
+use crate::signals;
+
+use std::cell::{Cell, RefCell};
 use std::env;
 use std::path::Path;
-use std::collections::HashMap;
-use std::io::Read;
-use std::str::FromStr;
+use std::io::{Read, Write};
+use std::os::unix::process::ExitStatusExt;
 use unicode_segmentation::UnicodeSegmentation;
 use which::which;
 
-fn arithmetic<S: AsRef<str>>(n: S, l: f32, r: f32) -> f32 {
+// deep recursion in user functions needs far more than the default stack; threads running brie ask for this much
+pub const STACK_SIZE: usize = 1 << 30;
+
+const RECURSION_LIMIT: usize = 1000;
+
+thread_local! {
+  // how deeply calls to user functions are nested
+  static DEPTH: Cell<usize> = const { Cell::new(0) };
+  // the exit status of each stage of the last pipeline, upstream first
+  static STATUSES: RefCell<Vec<i32>> = const { RefCell::new(Vec::new()) };
+}
+
+pub fn reset_status() {
+  STATUSES.with(|st| st.borrow_mut().clear());
+}
+
+fn record_status(codes: Vec<i32>) {
+  STATUSES.with(|st| *st.borrow_mut() = codes);
+}
+
+// pipefail: the rightmost stage which failed decides, so `false | cat` fails
+pub fn last_status() -> i32 {
+  STATUSES.with(|st| st.borrow().iter().rev().find(|c| **c != 0).copied().unwrap_or(0))
+}
+
+fn pipe_status() -> Vec<i32> {
+  STATUSES.with(|st| st.borrow().clone())
+}
+
+// the limit may be changed by assigning RECURSION_LIMIT
+fn enter_call(env: &Env, s: &String, loc: Location) -> Result<(), Error> {
+  let limit = match env.get("RECURSION_LIMIT") {
+    None => RECURSION_LIMIT,
+    Some(AST { variant: ASTVal::Num(n), .. }) if n.as_i64().is_some_and(|n| n >= 1) => n.as_i64().unwrap() as usize,
+    Some(v) => { return Err(error(ErrorKind::Type, s, loc, "Invalid recursion limit.", noteformat!("RECURSION_LIMIT must be a positive whole number.  It is:\n\r{}", v))); }
+  };
+
+  let depth = DEPTH.with(|d| d.get());
+  if depth >= limit {
+    return Err(error(ErrorKind::Recursion, s, loc, "Recursion limit exceeded.", noteformat!("Calls to functions were nested {} deep.  Assign RECURSION_LIMIT to allow more.", limit)));
+  }
+  DEPTH.with(|d| d.set(depth + 1));
+  Ok(())
+}
+
+fn leave_call() {
+  DEPTH.with(|d| d.set(d.get().saturating_sub(1)));
+}
+
+fn arithmetic<S: AsRef<str>>(n: S, l: &Number, r: &Number) -> Number {
   let n = n.as_ref();
   
-       if n == CHARSET::Plus   { l + r }
-  else if n == CHARSET::Minus  { l - r }
-  else if n == CHARSET::Times  { l * r }
-  else if n == CHARSET::Divide { l / r }
+       if n == CHARSET::Plus   { l.add(r) }
+  else if n == CHARSET::Minus  { l.sub(r) }
+  else if n == CHARSET::Times  { l.mul(r) }
+  else if n == CHARSET::Divide { l.div(r) }
     
   else { unreachable!() }
 }
@@ -31,168 +87,279 @@ fn is_arithmetic<S: AsRef<str>>(n: S) -> bool {
   || n == CHARSET::Divide
 }
 
-fn createcmd<R: Into<std::process::Stdio>, O: Into<std::process::Stdio>, E: Into<std::process::Stdio>, B: Into<std::process::Stdio>>(c: String, ags: Vec<AST>, stdin: R, stdout: O, stdoutcpy: O, stderr: E, stderrcpy: E, both: B, bothcpy: B, redirect: Redirect, s: &String, l: &Location) -> Result<std::process::Child, String> {
+fn createcmd<R: Into<std::process::Stdio>, O: Into<std::process::Stdio>, E: Into<std::process::Stdio>, B: Into<std::process::Stdio>>(c: String, ags: Vec<AST>, stdin: R, stdout: O, stdoutcpy: O, stderr: E, stderrcpy: E, both: B, bothcpy: B, redirect: Redirect, s: &String, l: &Location) -> Result<std::process::Child, Error> {
   let mut cmd = std::process::Command::new(c);
-  cmd.args(ags
+  cmd.args(flatten_args(ags)
       .into_iter()
       .map(|x| match x.variant {
-        ASTVal::String(str) | ASTVal::Symbol(str) => Ok(str),
+        ASTVal::String(str) => Ok(dirs::expand_tilde(&str)),
+        ASTVal::Symbol(str) => Ok(str),
         ASTVal::Num(i) => Ok(i.to_string()),
+        ASTVal::Bool(b) => Ok(b.to_string()),
         ASTVal::Command(..) => {
           let p = inpipe_to_ast(x, s, l, StdoutCaptureType::Data)?;
           match p.variant {
             ASTVal::String(str) | ASTVal::Symbol(str) => Ok(str),
             ASTVal::Num(i) => Ok(i.to_string()),
-            _ => Err(error(s, l.col, l.len, "Process requires valid arguments, but was not given one.", noteformat!("The given argument was:\n\r{}", p)))
+            _ => Err(error(ErrorKind::Type, s, *l, "Process requires valid arguments, but was not given one.", noteformat!("The given argument was:\n\r{}", p)))
           }
         },
-        _ => Err(error(s, l.col, l.len, "Process requires valid arguments, but was not given one.", noteformat!("The given argument was:\n\r{}", x)))
+        _ => Err(error(ErrorKind::Type, s, *l, "Process requires valid arguments, but was not given one.", noteformat!("The given argument was:\n\r{}", x)))
       })
       .collect::<Result<Vec<_>, _>>()?
     );
 
+  // both streams into one file must share a handle, or the second open clobbers the first
+  let stdoutfile = match redirect.stdout {
+    ToRedirect::File(ref f) => Some(openredirect(f, false, s, l)?),
+    ToRedirect::Append(ref f) => Some(openredirect(f, true, s, l)?),
+    _ => None
+  };
+  let stderrfile = match (&redirect.stderr, &redirect.stdout, &stdoutfile) {
+    (ToRedirect::File(f) | ToRedirect::Append(f), ToRedirect::File(f2) | ToRedirect::Append(f2), Some(sf)) if f == f2 => Some(sf.try_clone().map_err(|e| internalfailure(e, "clone file handle", s, l))?),
+    (ToRedirect::File(f), ..) => Some(openredirect(f, false, s, l)?),
+    (ToRedirect::Append(f), ..) => Some(openredirect(f, true, s, l)?),
+    _ => None
+  };
+
+  signals::setup_group(&mut cmd);
+  cmd.envs(redirect.env.iter().map(|(k, v)| (k, v)));
   cmd.stdin(stdin);
   match redirect.stdout {
     ToRedirect::ToStdout => cmd.stdout(stdout),
     ToRedirect::ToStderr => cmd.stdout(stderr),
     ToRedirect::ToBoth   => cmd.stdout(both),
-    ToRedirect::ToNull   => cmd.stdout(std::process::Stdio::null())
+    ToRedirect::ToNull   => cmd.stdout(std::process::Stdio::null()),
+    ToRedirect::File(_) | ToRedirect::Append(_) => cmd.stdout(stdoutfile.unwrap())
   };
   match redirect.stderr {
     ToRedirect::ToStdout => cmd.stderr(stdoutcpy),
     ToRedirect::ToStderr => cmd.stderr(stderrcpy),
     ToRedirect::ToBoth   => cmd.stderr(bothcpy),
-    ToRedirect::ToNull   => cmd.stderr(std::process::Stdio::null())
+    ToRedirect::ToNull   => cmd.stderr(std::process::Stdio::null()),
+    ToRedirect::File(_) | ToRedirect::Append(_) => cmd.stderr(stderrfile.unwrap())
   };
 
-  let ch = cmd.spawn().map_err(|e| error(s, l.col, l.len, "Failed to spawn command.", noteformat!("Trace:\n\r{}", e)))?; drop(cmd);
+  let ch = cmd.spawn().map_err(|e| error(ErrorKind::Io, s, *l, "Failed to spawn command.", noteformat!("Trace:\n\r{}", e)))?; drop(cmd);
+  jobs::register(ch.id());
+  signals::joined(ch.id());
   Ok(ch)
 }
 
+fn openredirect(f: &String, append: bool, s: &String, l: &Location) -> Result<std::fs::File, Error> {
+  std::fs::OpenOptions::new()
+    .write(true)
+    .create(true)
+    .append(append)
+    .truncate(!append)
+    .open(f)
+    .map_err(|e| error(ErrorKind::Io, s, *l, "Failed to open file for redirection.", noteformat!("The file was {}.  Trace:\n\r{}", f, e)))
+}
+
+fn is_file_redirect(r: &ToRedirect) -> bool {
+  matches!(r, ToRedirect::File(_) | ToRedirect::Append(_))
+}
+
 struct CmdOutput {
   stdout: os_pipe::PipeReader,
   stderrwriter: os_pipe::PipeWriter,
   stderrreader: os_pipe::PipeReader,
-  child: std::process::Child
+  child: std::process::Child,
+  // the stages feeding this one, upstream first
+  upstream: Vec<std::process::Child>
 }
 
-fn spawncmd<'a>(c: String, ags: Vec<AST>, stdin: Box<AST>, red: Redirect, scope: &'a std::thread::Scope<'a, '_>, s: &'a String, l: &'a Location) -> Result<CmdOutput, String> {
+fn spawncmd<'a>(c: String, ags: Vec<AST>, stdin: Box<AST>, red: Redirect, scope: &'a std::thread::Scope<'a, '_>, s: &'a String, l: &'a Location) -> Result<CmdOutput, Error> {
   let (stdoutreader, mut stdoutwriter) = os_pipe::pipe().map_err(|e| internalfailure(e, "open pipe", s, l))?;
   let (stderrreader, stderrwriter);
   let (mut bothreader, bothwriter) = os_pipe::pipe().map_err(|e| internalfailure(e, "open pipe", s, l))?;
   
-  let ch;
-  match stdin.variant {
+  let mut upstream = Vec::new();
+  
+  let stdin: std::process::Stdio = match stdin.variant {
     ASTVal::Command(sc, sags, sstdin, sred) => {
-      let CmdOutput { stdout: stdinreader, stderrreader: tempstderrreader, stderrwriter: tempstderrwriter, child: _ch } = spawncmd(sc, sags, sstdin, sred, scope, s, l)?;
+      let CmdOutput { stdout: stdinreader, stderrreader: tempstderrreader, stderrwriter: tempstderrwriter, child: upch, upstream: ups } = spawncmd(sc, sags, sstdin, sred, scope, s, l)?;
       stderrreader = tempstderrreader; // these lines are
       stderrwriter = tempstderrwriter; // quite annoying
+      upstream = ups;
+      upstream.push(upch);
+      
+      stdinreader.into()
+    },
+    ASTVal::Array(ref vs) if vs.is_empty() => {
+      (stderrreader, stderrwriter) = os_pipe::pipe().map_err(|e| internalfailure(e, "open pipe", s, l))?;
       
-      ch = createcmd(c, ags, stdinreader,
-                     stdoutwriter.try_clone().map_err(|e| internalfailure(e, "clone pipe", s, l))?,
-                     stdoutwriter.try_clone().map_err(|e| internalfailure(e, "clone pipe", s, l))?,
-                     stderrwriter.try_clone().map_err(|e| internalfailure(e, "clone pipe", s, l))?,
-                     stderrwriter.try_clone().map_err(|e| internalfailure(e, "clone pipe", s, l))?,
-                     bothwriter.try_clone().map_err(|e| internalfailure(e, "clone pipe", s, l))?,
-                     bothwriter, red, s, l)?;
+      std::process::Stdio::inherit()
     },
     _ => {
+      let data = inpipe_to_ast(*stdin, s, l, StdoutCaptureType::Data)?;
+      let text = ast_serialize(data, s, *l)?;
+      
+      let (stdinreader, mut stdinwriter) = os_pipe::pipe().map_err(|e| internalfailure(e, "open pipe", s, l))?;
       (stderrreader, stderrwriter) = os_pipe::pipe().map_err(|e| internalfailure(e, "open pipe", s, l))?;
+
+      // a child which never reads its stdin closes the pipe on exit; the broken pipe just ends the writer
+      scope.spawn(move || -> Result<(), Error> {
+        stdinwriter.write_all(text.as_bytes()).map_err(|e| internalfailure(e, "write to process stdin", s, l))?;
+        Ok(())
+      });
       
-      ch = createcmd(c, ags, std::process::Stdio::inherit(), 
-                     stdoutwriter.try_clone().map_err(|e| internalfailure(e, "clone pipe", s, l))?,
-                     stdoutwriter.try_clone().map_err(|e| internalfailure(e, "clone pipe", s, l))?,
-                     stderrwriter.try_clone().map_err(|e| internalfailure(e, "clone pipe", s, l))?,
-                     stderrwriter.try_clone().map_err(|e| internalfailure(e, "clone pipe", s, l))?,
-                     bothwriter.try_clone().map_err(|e| internalfailure(e, "clone pipe", s, l))?,
-                     bothwriter, red, s, l)?;
+      stdinreader.into()
     }
-  }
+  };
+
+  // an input file takes the place of whatever was piped in
+  let stdin = match red.stdin {
+    Some(ref f) => reap_on_err(std::fs::File::open(f).map_err(|e| error(ErrorKind::Io, s, *l, "Failed to open file for redirection.", noteformat!("The file was {}.  Trace:\n\r{}", f, e))), &mut upstream)?.into(),
+    None => stdin
+  };
   
-  let mut brcl = bothreader.try_clone().map_err(|e| internalfailure(e, "clone pipe", s, l))?;
-  scope.spawn(move || -> Result<(), String> {
+  // every handle is cloned before the process starts, so a failed clone only has upstream to reap
+  let clone = |w: &os_pipe::PipeWriter| w.try_clone().map_err(|e| internalfailure(e, "clone pipe", s, l));
+  let (out, outcl, err, errcl, both, mut secl, mut brcl) = reap_on_err((|| -> Result<_, Error> {
+    Ok((clone(&stdoutwriter)?, clone(&stdoutwriter)?, clone(&stderrwriter)?, clone(&stderrwriter)?, clone(&bothwriter)?, clone(&stderrwriter)?,
+        bothreader.try_clone().map_err(|e| internalfailure(e, "clone pipe", s, l))?))
+  })(), &mut upstream)?;
+
+  let ch = reap_on_err(createcmd(c, ags, stdin, out, outcl, err, errcl, both, bothwriter, red, s, l), &mut upstream)?;
+  
+  scope.spawn(move || -> Result<(), Error> {
     std::io::copy(&mut brcl, &mut stdoutwriter).map_err(|e| internalfailure(e, "copy pipe stream", s, l))?;
     Ok(())
   });
-  let mut secl = stderrwriter.try_clone().map_err(|e| error(s, l.col, l.len, "Failed to clone pipe.", noteformat!("Trace:\n\r{}", e)))?;
-  scope.spawn(move || -> Result<(), String> {
+  scope.spawn(move || -> Result<(), Error> {
     std::io::copy(&mut bothreader, &mut secl).map_err(|e| internalfailure(e, "copy pipe stream", s, l))?;
     Ok(())
   });
 
-  Ok(CmdOutput { stdout: stdoutreader, stderrreader, stderrwriter, child: ch })
+  Ok(CmdOutput { stdout: stdoutreader, stderrreader, stderrwriter, child: ch, upstream })
+}
+
+// stages already running when a later one fails to start are stopped and waited on, leaving no zombies
+fn reap_on_err<T>(res: Result<T, Error>, upstream: &mut Vec<std::process::Child>) -> Result<T, Error> {
+  if res.is_err() {
+    for mut ch in upstream.drain(..) {
+      let _ = ch.kill();
+      let _ = ch.wait();
+      jobs::reaped(ch.id());
+    }
+  }
+  res
 }
 
 #[derive(Clone, Copy)]
 enum StdoutCaptureType {
   None,
   Data,
-  All
+  All,
+  Stderr // output goes to the terminal as it comes, and stderr is kept as well
+}
+
+// a process killed by a signal reports 128 plus the signal, as in other shells
+fn exit_number(status: std::process::ExitStatus) -> Option<i32> {
+  status.code().or(status.signal().map(|sig| 128 + sig))
 }
 
-fn inpipe_to_ast(v: AST, s: &String, l: &Location, needs_stdout_capture: StdoutCaptureType) -> Result<AST, String>{
+fn inpipe_to_ast(v: AST, s: &String, l: &Location, needs_stdout_capture: StdoutCaptureType) -> Result<AST, Error>{
   match v.variant {
     ASTVal::Command(c, ags, stdin, red) => {
       let mut outstring = String::new();
       let mut stderrstring = String::new();
       
-      let mut ch = std::thread::scope(|scope| -> Result<std::process::Child, String> {
-        let CmdOutput { stdout: mut so, child: ch, stderrreader: mut se, stderrwriter: _sw } = spawncmd(c, ags, stdin, red, scope, s, l)?;
+      let outer = signals::begin_pipeline();
+      let statuses = std::thread::scope(|scope| -> Result<Vec<std::process::ExitStatus>, Error> {
+        let CmdOutput { stdout: mut so, child: ch, stderrreader: mut se, stderrwriter: _sw, upstream: mut chs } = spawncmd(c, ags, stdin, red, scope, s, l)?;
+        chs.push(ch);
 
         let ostrref = &mut outstring;
         let errstrref = &mut stderrstring;
         
         match needs_stdout_capture {
           StdoutCaptureType::None => {
-            scope.spawn(move || -> Result<(), String> {
+            scope.spawn(move || -> Result<(), Error> {
               std::io::copy(&mut so, &mut std::io::stdout()).map_err(|e| internalfailure(e, "copy pipe stream", s, l))?;
               Ok(())
             });
 
-            scope.spawn(move || -> Result<(), String> {
+            scope.spawn(move || -> Result<(), Error> {
               std::io::copy(&mut se, &mut std::io::stderr()).map_err(|e| internalfailure(e, "copy pipe stream", s, l))?;
               Ok(())
             });
           },
           StdoutCaptureType::Data => {
-            scope.spawn(move || -> Result<(), String> {
+            scope.spawn(move || -> Result<(), Error> {
               so.read_to_string(ostrref).map_err(|e| internalfailure(e, "read stdout to string", s, l))?;
               Ok(())
             });
 
-            scope.spawn(move || -> Result<(), String> {
+            scope.spawn(move || -> Result<(), Error> {
               std::io::copy(&mut se, &mut std::io::stderr()).map_err(|e| internalfailure(e, "copy pipe stream", s, l))?;
               Ok(())
             });
           },
           StdoutCaptureType::All => {
-            scope.spawn(move || -> Result<(), String> {
+            scope.spawn(move || -> Result<(), Error> {
               so.read_to_string(ostrref).map_err(|e| internalfailure(e, "read stdout to string", s, l))?;
               Ok(())
             });
 
-            scope.spawn(move || -> Result<(), String> {
+            scope.spawn(move || -> Result<(), Error> {
               se.read_to_string(errstrref).map_err(|e| internalfailure(e, "read stderr to string", s, l))?;
               Ok(())
             });
+          },
+          StdoutCaptureType::Stderr => {
+            scope.spawn(move || -> Result<(), Error> {
+              std::io::copy(&mut so, &mut std::io::stdout()).map_err(|e| internalfailure(e, "copy pipe stream", s, l))?;
+              Ok(())
+            });
+
+            scope.spawn(move || -> Result<(), Error> {
+              let (mut buf, mut kept) = ([0; 4096], Vec::new());
+              loop {
+                let n = se.read(&mut buf).map_err(|e| internalfailure(e, "read stderr", s, l))?;
+                if n == 0 { break; }
+                std::io::stderr().write_all(&buf[..n]).map_err(|e| internalfailure(e, "copy pipe stream", s, l))?;
+                kept.extend_from_slice(&buf[..n]);
+              }
+              *errstrref = String::from_utf8_lossy(&kept).into_owned();
+              Ok(())
+            });
           }
         };
 
-        Ok(ch)
-      })?;
-  
-      let exitcode = ch.wait().map_err(|e| internalfailure(e, "wait for process", s, l))?;
+        // waited on while the streams are still being copied, since a stopped stage holds its pipes open.
+        // every stage is waited on, even after one of the waits fails
+        chs.into_iter()
+          .map(|mut ch| signals::wait(&mut ch).map_err(|e| internalfailure(e, "wait for process", s, l)))
+          .collect::<Vec<Result<std::process::ExitStatus, Error>>>()
+          .into_iter().collect::<Result<Vec<std::process::ExitStatus>, Error>>()
+      });
+      signals::end_pipeline(outer);
+      let statuses = statuses?;
+      let exitcode = *statuses.last().unwrap();
+      record_status(statuses.iter().map(|st| exit_number(*st).unwrap_or(-1)).collect());
 
       Ok(match needs_stdout_capture {
-        StdoutCaptureType::None => AST { variant: ASTVal::Num(exitcode.code().ok_or(internalfailure("[nil]", "read exit code", s, l))? as f32), location: *l },
+        StdoutCaptureType::None => AST { variant: ASTVal::Num(Number::from(exit_number(exitcode).ok_or(internalfailure("[nil]", "read exit code", s, l))? as i64)), location: *l },
         StdoutCaptureType::Data => {
           AST { variant: ASTVal::String(outstring), location: *l }
         },
         StdoutCaptureType::All => {
           AST { variant: ASTVal::Array(Vec::from([
             AST { variant: ASTVal::String(outstring), location: *l },
-            AST { variant: ASTVal::Num(exitcode.code().ok_or(internalfailure("[nil]", "read exit code", s, l))? as f32), location: *l },
-            AST { variant: ASTVal::String(stderrstring), location: l.clone() }
+            AST { variant: ASTVal::Num(Number::from(exit_number(exitcode).ok_or(internalfailure("[nil]", "read exit code", s, l))? as i64)), location: *l },
+            AST { variant: ASTVal::String(stderrstring), location: *l },
+            // the status of every stage, upstream first, even for a single command
+            AST { variant: ASTVal::Array(statuses.iter().map(|st| Ok(AST { variant: ASTVal::Num(Number::from(exit_number(*st).ok_or(internalfailure("[nil]", "read exit code", s, l))? as i64)), location: *l })).collect::<Result<Vec<AST>, Error>>()?), location: *l }
+          ])), location: *l }
+        },
+        StdoutCaptureType::Stderr => {
+          // the rightmost stage which failed decides, as for STATUS
+          let code = statuses.iter().rev().map(|st| exit_number(*st).unwrap_or(-1)).find(|c| *c != 0).unwrap_or(0);
+          AST { variant: ASTVal::Array(Vec::from([
+            AST { variant: ASTVal::Num(Number::from(code as i64)), location: *l },
+            AST { variant: ASTVal::String(stderrstring), location: *l }
           ])), location: *l }
         }
       })
@@ -208,19 +375,192 @@ fn inpipe_to_ast(v: AST, s: &String, l: &Location, needs_stdout_capture: StdoutC
   }
 }
 
+// a background job must not fight the repl for the terminal, so stages that would inherit stdin read nothing
+fn detach_stdin(v: AST) -> AST {
+  match v.variant {
+    ASTVal::Command(c, ags, stdin, mut red) => {
+      let stdin = match stdin.variant {
+        ASTVal::Command(..) => Box::new(detach_stdin(*stdin)),
+        ASTVal::Array(ref vs) if vs.is_empty() && red.stdin.is_none() => {
+          red.stdin = Some("/dev/null".to_string());
+          stdin
+        },
+        _ => stdin
+      };
+      AST { variant: ASTVal::Command(c, ags, stdin, red), location: v.location }
+    },
+    _ => v
+  }
+}
+
+// none for a job which is stopped, and so will not finish until it is continued
+fn job_result(id: usize, s: &String, loc: Location) -> Result<Option<AST>, Error> {
+  match jobs::wait(id) {
+    Some(jobs::JobStatus::Done(Err(e))) => Err(error(ErrorKind::Job, s, loc, "Job failed.", noteformat!("Job {} stopped with an error.", id)).caused_by(e)),
+    Some(jobs::JobStatus::Done(res)) => res.map(Some),
+    Some(_) => Ok(None),
+    None => Err(error(ErrorKind::Job, s, loc, "No such job.", noteformat!("There is no job {} in the job table.", id)))
+  }
+}
+
+// errors caught by `try` become (kind message line col), numbered as in the error's header
+fn ast_from_error(e: &Error, loc: Location) -> AST {
+  let (line, col) = match e.span {
+    Some(ref span) => (span.file.as_ref().map(|f| f.1).unwrap_or(0) + span.location.line + 1, span.location.col + 1),
+    None => (0, 0)
+  };
+  AST { variant: ASTVal::Array(Vec::from([
+    AST { variant: ASTVal::String(e.kind.name().to_string()), location: loc },
+    AST { variant: ASTVal::String(error::strip_color(&e.message)), location: loc },
+    AST { variant: ASTVal::Num(Number::from(line as i64)), location: loc },
+    AST { variant: ASTVal::Num(Number::from(col as i64)), location: loc }
+  ])), location: loc }
+}
+
+// a process run under `try` fails by its exit code, and otherwise gives it as a process on its own would.
+// its output goes to the terminal as it runs, and its stderr is kept for the error
+fn try_to_ast(v: AST, s: &String, loc: Location) -> Result<AST, Error> {
+  match v.variant {
+    ASTVal::Command(ref c, ..) => {
+      let name = c.clone();
+      let res = inpipe_to_ast(v, s, &loc, StdoutCaptureType::Stderr)?;
+      match res.variant {
+        ASTVal::Array(mut vs) if vs.len() == 2 => {
+          let (stderr, code) = (vs.pop().unwrap(), vs.pop().unwrap());
+          let stderr = match stderr.variant { ASTVal::String(e) => e, _ => String::new() };
+          match code.variant {
+            ASTVal::Num(ref n) if !n.is_zero() => Err(error(ErrorKind::Process, s, loc, format!("{} exited with status {}.", name, n), noteformat!("Its stderr was:\n\r{}", stderr.trim_end()))),
+            _ => Ok(code)
+          }
+        },
+        _ => Ok(res)
+      }
+    },
+    _ => inpipe_to_ast(v, s, &loc, StdoutCaptureType::Data)
+  }
+}
+
+fn dir_stack(loc: Location) -> AST {
+  AST { variant: ASTVal::Array(dirs::list().into_iter().map(|d| AST { variant: ASTVal::String(d), location: loc }).collect()), location: loc }
+}
+
+// nested arrays, as from glob, spread into separate arguments
+fn flatten_args(ags: Vec<AST>) -> Vec<AST> {
+  ags.into_iter().flat_map(|a| match a.variant {
+    ASTVal::Array(vs) => flatten_args(vs),
+    _ => Vec::from([a])
+  }).collect()
+}
+
+// what a process sees of a value in its environment
+fn env_value(v: &AST) -> Option<String> {
+  match &v.variant {
+    ASTVal::String(x) if !x.contains('\0') => Some(x.to_string()),
+    ASTVal::Num(n) => Some(n.to_string()),
+    ASTVal::Bool(b) => Some(b.to_string()),
+    _ => None
+  }
+}
+
+fn dict_get<'a>(es: &'a [(String, AST)], k: &str) -> Option<&'a AST> {
+  es.iter().find(|(k2, _)| k2 == k).map(|(_, v)| v)
+}
+
+// a key given again keeps its place and takes the new value
+fn dict_insert(es: &mut Vec<(String, AST)>, k: String, v: AST) {
+  match es.iter_mut().find(|(k2, _)| *k2 == k) {
+    Some(e) => { e.1 = v; },
+    None => { es.push((k, v)); }
+  }
+}
+
+// a table's rows, each as a dict of its columns
+fn table_rows(cols: &[String], rows: Vec<Vec<AST>>, loc: Location) -> Vec<AST> {
+  rows.into_iter().map(|r| AST { variant: ASTVal::Dict(cols.iter().cloned().zip(r).collect()), location: loc }).collect()
+}
+
+// dicts as rows, their keys as columns in the order first seen; a key missing from a row is null there
+fn table_from_dicts(ds: Vec<Vec<(String, AST)>>, loc: Location) -> AST {
+  let mut cols: Vec<String> = Vec::new();
+  for (k, _) in ds.iter().flatten() {
+    if !cols.contains(k) {
+      cols.push(k.to_string());
+    }
+  }
+
+  let rows = ds.iter().map(|es| cols.iter().map(|c| dict_get(es, c).cloned().unwrap_or(AST { variant: ASTVal::Null, location: loc })).collect()).collect();
+  AST { variant: ASTVal::Table(cols, rows), location: loc }
+}
+
+// the first row names the columns.  short rows are padded with null, and fields past the names get columns named by position
+// a field of text read into a table is a number when it reads back as the same text, so 30 is a number
+// but 030, 1e3 and 1.50 stay strings, and are written out again unchanged
+fn table_field(text: String, loc: Location) -> AST {
+  let plain = !text.is_empty() && text.chars().all(|c| c.is_ascii_digit() || c == '.' || c == '-');
+  match Number::parse(&text) {
+    Some(n) if plain && n.to_string() == text => AST { variant: ASTVal::Num(n), location: loc },
+    _ => AST { variant: ASTVal::String(text), location: loc }
+  }
+}
+
+fn table_from_header<S: AsRef<str>>(mut rows: Vec<Vec<AST>>, s: S, loc: Location) -> Result<AST, Error> {
+  let mut cols = if rows.is_empty() { Vec::new() } else { rows.remove(0).into_iter().map(|c| ast_stringify(c, s.as_ref(), loc)).collect::<Result<Vec<String>, Error>>()? };
+  let width = rows.iter().map(|r| r.len()).max().unwrap_or(0);
+  for i in cols.len()..width {
+    cols.push(i.to_string());
+  }
+
+  for r in rows.iter_mut() {
+    r.resize(cols.len(), AST { variant: ASTVal::Null, location: loc });
+  }
+  Ok(AST { variant: ASTVal::Table(cols, rows), location: loc })
+}
+
+// the column names as a first row, then the rows
+fn table_to_arrays(cols: Vec<String>, rows: Vec<Vec<AST>>, loc: Location) -> AST {
+  let header = AST { variant: ASTVal::Array(cols.into_iter().map(|c| AST { variant: ASTVal::String(c), location: loc }).collect()), location: loc };
+  AST { variant: ASTVal::Array(Some(header).into_iter().chain(rows.into_iter().map(|r| AST { variant: ASTVal::Array(r), location: loc })).collect()), location: loc }
+}
+
+// a table as an array of its rows, for those which go by row alone
+fn table_as_rows(v: AST) -> AST {
+  match v.variant {
+    ASTVal::Table(_, rs) => AST { variant: ASTVal::Array(rs.into_iter().map(|r| AST { variant: ASTVal::Array(r), location: v.location }).collect()), location: v.location },
+    _ => v
+  }
+}
+
+// what each row of a table gave back; dicts make a table again
+fn from_row_results(res: Vec<AST>, loc: Location) -> AST {
+  if !res.is_empty() && res.iter().all(|r| matches!(r.variant, ASTVal::Dict(_))) {
+    table_from_dicts(res.into_iter().map(|r| match r.variant {
+      ASTVal::Dict(es) => es,
+      _ => unreachable!()
+    }).collect(), loc)
+  } else {
+    AST { variant: ASTVal::Array(res), location: loc }
+  }
+}
+
+// set_var panics on these
+fn valid_env_name(k: &str) -> bool {
+  !k.is_empty() && !k.contains('=') && !k.contains('\0')
+}
+
 fn unoptionize(node: Option<AST>) -> AST {
-  node.unwrap_or(AST { variant: ASTVal::Array(Vec::new()), location: Location { col: 0, len: 0 }})
+  node.unwrap_or(AST { variant: ASTVal::Array(Vec::new()), location: Location { col: 0, len: 0, line: 0 }})
 }
 
 fn nilarr() -> AST {
-  AST { variant: ASTVal::Array(Vec::new()), location: Location { col: 0, len: 0 }}
+  AST { variant: ASTVal::Array(Vec::new()), location: Location { col: 0, len: 0, line: 0 }}
 }
 
 fn is_truthy(n: &AST) -> bool {
-  if let ASTVal::Num(x) = n.variant { // empty array?
-    return x != 0.0;
-  } else {
-    return true;
+  match n.variant { // empty array?
+    ASTVal::Num(ref x) => !x.is_zero(),
+    ASTVal::Bool(b) => b,
+    ASTVal::Null => false,
+    _ => true
   }
 }
 
@@ -257,11 +597,14 @@ fn equality(l: &AST, r: &AST) -> bool {
   }
 
   match &l.variant {
-    ASTVal::Assign(i, v) => if let ASTVal::Assign(ref i2, ref v2) = r.variant {
-      i == i2 && equality(&v, &v2)
+    ASTVal::Assign(i, v, o) => if let ASTVal::Assign(ref i2, ref v2, ref o2) = r.variant {
+      i == i2 && o == o2 && equality(v, v2)
     } else { unreachable!() },
-    ASTVal::AliasAssign(i, v) => if let ASTVal::AliasAssign(ref i2, ref v2) = r.variant {
-      i == i2 && equality(&v, &v2)
+    ASTVal::AliasAssign(i, v, o) => if let ASTVal::AliasAssign(ref i2, ref v2, ref o2) = r.variant {
+      i == i2 && o == o2 && equality(v, v2)
+    } else { unreachable!() },
+    ASTVal::Block(vs) => if let ASTVal::Block(ref vs2) = r.variant {
+      vs.len() == vs2.len() && vs.iter().zip(vs2).all(|(a, b)| equality(a, b))
     } else { unreachable!() },
     ASTVal::Num(i) => if let ASTVal::Num(ref i2) = r.variant {
       i == i2
@@ -317,21 +660,62 @@ fn equality(l: &AST, r: &AST) -> bool {
         }
       }
       a == a2 && equality(&c, &c2) && d == d2
+    } else { unreachable!() },
+    ASTVal::Job(id) => if let ASTVal::Job(ref id2) = r.variant {
+      id == id2
+    } else { unreachable!() },
+    ASTVal::Bool(b) => if let ASTVal::Bool(ref b2) = r.variant {
+      b == b2
+    } else { unreachable!() },
+    ASTVal::Null => true,
+    // the same keys with equal values, in any order
+    ASTVal::Dict(es) => if let ASTVal::Dict(ref es2) = r.variant {
+      es.len() == es2.len() && es.iter().all(|(k, v)| dict_get(es2, k).is_some_and(|v2| equality(v, v2)))
+    } else { unreachable!() },
+    ASTVal::Table(cs, rs) => if let ASTVal::Table(ref cs2, ref rs2) = r.variant {
+      cs == cs2 && rs.len() == rs2.len() && rs.iter().zip(rs2).all(|(a, b)| a.iter().zip(b).all(|(x, y)| equality(x, y)))
+    } else { unreachable!() },
+    ASTVal::Lambda(l, n, body, _) => if let ASTVal::Lambda(ref l2, ref n2, ref body2, _) = r.variant {
+      l == l2 && n == n2 && equality(body, body2)
     } else { unreachable!() }
   }
 }
 
+// values of different kinds sort by kind: null, then bools, numbers, strings, symbols, arrays, dicts, and tables
+fn kind_rank(v: &ASTVal) -> u8 {
+  match v {
+    ASTVal::Null => 0,
+    ASTVal::Bool(_) => 1,
+    ASTVal::Num(_) => 2,
+    ASTVal::String(_) => 3,
+    ASTVal::Symbol(_) => 4,
+    ASTVal::Array(_) => 5,
+    ASTVal::Dict(_) => 6,
+    ASTVal::Table(..) => 7,
+    _ => 8
+  }
+}
+
+// element by element, and then by length
+fn compare_all(l: &[AST], r: &[AST]) -> std::cmp::Ordering {
+  l.iter().zip(r).map(|(a, b)| compare(a, b)).find(|o| o.is_ne()).unwrap_or(l.len().cmp(&r.len()))
+}
+
 fn compare(l: &AST, r: &AST) -> std::cmp::Ordering {
-  if let (ASTVal::Num(l), ASTVal::Num(r)) = (&l.variant, &r.variant) {
-    match () {
+  match (&l.variant, &r.variant) {
+    (ASTVal::Num(l), ASTVal::Num(r)) => match () {
       _ if l < r => std::cmp::Ordering::Less,
       _ if l > r => std::cmp::Ordering::Greater,
       _ => std::cmp::Ordering::Equal
-    }
-  } else if let (ASTVal::String(l), ASTVal::String(r)) = (&l.variant, &r.variant) {
-    l.to_lowercase().cmp(&r.to_lowercase())
-  } else {
-    std::cmp::Ordering::Equal
+    },
+    (ASTVal::String(l), ASTVal::String(r)) => l.to_lowercase().cmp(&r.to_lowercase()),
+    (ASTVal::Symbol(l), ASTVal::Symbol(r)) => l.cmp(r),
+    (ASTVal::Bool(l), ASTVal::Bool(r)) => l.cmp(r),
+    (ASTVal::Array(l), ASTVal::Array(r)) => compare_all(l, r),
+    // by each key in turn and then its value
+    (ASTVal::Dict(l), ASTVal::Dict(r)) => l.iter().zip(r).map(|((lk, lv), (rk, rv))| lk.cmp(rk).then_with(|| compare(lv, rv))).find(|o| o.is_ne()).unwrap_or(l.len().cmp(&r.len())),
+    (ASTVal::Table(lc, lr), ASTVal::Table(rc, rr)) => lc.cmp(rc).then_with(|| lr.iter().zip(rr).map(|(a, b)| compare_all(a, b)).find(|o| o.is_ne()).unwrap_or(lr.len().cmp(&rr.len()))),
+    (l, r) => kind_rank(l).cmp(&kind_rank(r))
   }
 }
 
@@ -372,11 +756,12 @@ fn shapeof(node: &AST) -> Vec<usize> {
       l.insert(0, vs.len());
       l
     },
+    ASTVal::Table(cs, rs) => Vec::from([rs.len(), cs.len()]),
     _ => Vec::new()
   }
 }
 
-fn set_val<S: AsRef<str>>(arr: &mut AST, mut indices: Vec<usize>, val: AST, s: S) -> Result<(), String> {
+fn set_val<S: AsRef<str>>(arr: &mut AST, mut indices: Vec<usize>, val: AST, s: S) -> Result<(), Error> {
   if indices.len() < 1 {
     *arr = val;
     return Ok(());
@@ -387,13 +772,13 @@ fn set_val<S: AsRef<str>>(arr: &mut AST, mut indices: Vec<usize>, val: AST, s: S
       let index = indices.remove(0);
       set_val(&mut vs[index], indices, val, s)
     },
-    _ => Err(error(s, arr.location.col, arr.location.len, "Expected an array to index into but found a value.", noteformat!("The value was:\n\r{}", arr)))
+    _ => Err(error(ErrorKind::Type, s, arr.location, "Expected an array to index into but found a value.", noteformat!("The value was:\n\r{}", arr)))
   }?;
 
   Ok(())
 }
 
-fn transposify<S: AsRef<str>>(dest: &mut AST, source: AST, axes: &Vec<usize>, mut this_index: Vec<usize>, s: S) -> Result<(), String> {
+fn transposify<S: AsRef<str>>(dest: &mut AST, source: AST, axes: &Vec<usize>, mut this_index: Vec<usize>, s: S) -> Result<(), Error> {
   match source.variant {
     ASTVal::Array(vs) => {
       this_index.push(0);
@@ -407,7 +792,7 @@ fn transposify<S: AsRef<str>>(dest: &mut AST, source: AST, axes: &Vec<usize>, mu
       let mut result_index = Vec::new();
       for a in axes {
         if *a >= this_index.len() {
-          return Err(error(s, source.location.col, source.location.len, "Malformed shape.", noteformat!("Argument to transpose is malformed for transposition.  The value is:\n\r{}", source)));
+          return Err(error(ErrorKind::Value, s, source.location, "Malformed shape.", noteformat!("Argument to transpose is malformed for transposition.  The value is:\n\r{}", source)));
         }
         result_index.push(this_index[*a].clone());
       }
@@ -418,7 +803,7 @@ fn transposify<S: AsRef<str>>(dest: &mut AST, source: AST, axes: &Vec<usize>, mu
   Ok(())
 }
 
-fn transpose<S: AsRef<str>>(axes: Option<Vec<usize>>, node: AST, s: S) -> Result<AST, String> {
+fn transpose<S: AsRef<str>>(axes: Option<Vec<usize>>, node: AST, s: S) -> Result<AST, Error> {
   let shape = shapeof(&node);
 
   let axes = match axes {
@@ -430,7 +815,7 @@ fn transpose<S: AsRef<str>>(axes: Option<Vec<usize>>, node: AST, s: S) -> Result
     let mut r = Vec::new();
     for a in &axes {
       if *a >= shape.len() {
-        return Err(error(s, node.location.col, node.location.len, "Shape out of bounds.", "Transposition axes lie outside the boundaries of the shape of the given argument."));
+        return Err(error(ErrorKind::Index, s, node.location, "Shape out of bounds.", "Transposition axes lie outside the boundaries of the shape of the given argument."));
       }
       r.push(shape[*a]);
     }
@@ -439,7 +824,7 @@ fn transpose<S: AsRef<str>>(axes: Option<Vec<usize>>, node: AST, s: S) -> Result
 
   let mut template = Vec::new();
   for _ in 0..reordered_shape.iter().map(|i| *i).reduce(|l, r| l * r).unwrap_or(0) {
-    template.push(AST { variant: ASTVal::Num(0.0), location: node.location });
+    template.push(AST { variant: ASTVal::Num(Number::from(0)), location: node.location });
   }
   let mut vind = 0;
   let mut res = reshape(&template, &mut vind, &reordered_shape.iter().map(|i| *i as i32).collect(), 0, node.location);
@@ -449,46 +834,207 @@ fn transpose<S: AsRef<str>>(axes: Option<Vec<usize>>, node: AST, s: S) -> Result
   Ok(res)
 }
 
-fn ast_stringify<S: AsRef<str>>(node: AST, s: S, loc: Location) -> Result<String, String> {
+fn ast_stringify<S: AsRef<str>>(node: AST, s: S, loc: Location) -> Result<String, Error> {
   match node.variant {
     ASTVal::String(s) => Ok(format!("{}", s)),
     ASTVal::Symbol(s) => Ok(format!("--{}", s)),
     ASTVal::Ident(i) => Ok(format!("{}", i)),
     ASTVal::Num(n) => Ok(format!("{}", n)),
+    ASTVal::Bool(b) => Ok(format!("{}", b)),
+    // an empty field, as in csv
+    ASTVal::Null => Ok(String::new()),
     
-    _ => Err(error(s, loc.col, loc.len, "Invalid item to stringify.", noteformat!("This item must be atomic.  Try using a conversion method first like `list` or `json`.  The given object was:\n\r{}", node)))
+    _ => Err(error(ErrorKind::Type, s, loc, "Invalid item to stringify.", noteformat!("This item must be atomic.  Try using a conversion method first like `list` or `json`.  The given object was:\n\r{}", node)))
+  }
+}
+
+// the text fed to a process' stdin.  strings go in verbatim; arrays follow `list`, one line per element
+// and tab-separated fields for nested arrays, with dicts and anything nested deeper written as JSON.
+// a table is its header line and then its rows, as with `list`
+fn ast_serialize<S: AsRef<str>>(node: AST, s: S, loc: Location) -> Result<String, Error> {
+  match node.variant {
+    ASTVal::String(st) => Ok(st),
+    ASTVal::Table(cs, rs) => ast_serialize(table_to_arrays(cs, rs, loc), s, loc),
+    ASTVal::Dict(_) => json_write(node, s.as_ref()).map(|x| x + "\n"),
+    ASTVal::Array(vs) => Ok(vs.into_iter().map(|e| match e.variant {
+      ASTVal::Dict(_) => json_write(e, s.as_ref()).map(|x| x + "\n"),
+      ASTVal::Array(vs2) => vs2.into_iter().map(|e| match e.variant {
+        ASTVal::Array(_) | ASTVal::Dict(_) => json_write(e, s.as_ref()),
+        _ => ast_stringify(e, s.as_ref(), loc)
+      }).collect::<Result<Vec<String>, Error>>().map(|x| x.join("\t") + "\n"),
+      _ => ast_stringify(e, s.as_ref(), loc).map(|x| x + "\n")
+    }).collect::<Result<Vec<String>, Error>>()?.concat()),
+    _ => ast_stringify(node, s, loc).map(|x| x + "\n")
   }
 }
 
-fn ast_from_jsonvalue(json: json::JsonValue, location: Location) -> AST {
-  match json {
-    json::JsonValue::Null => AST { variant: ASTVal::Symbol("--Null".to_string()), location },
-    json::JsonValue::Short(s) => AST { variant: ASTVal::String(s.into()), location }, json::JsonValue::String(s) => AST { variant: ASTVal::String(s), location },
-    json::JsonValue::Number(n) => AST { variant: ASTVal::Num(n.into()), location },
-    json::JsonValue::Boolean(b) => AST { variant: ASTVal::Num(b as i32 as f32 * 1.0), location },
-    json::JsonValue::Array(a) => AST { variant: ASTVal::Array(a.into_iter().map(|v| ast_from_jsonvalue(v, location)).collect()), location },
-    json::JsonValue::Object(_) => {
-      let (mut keys, mut vals) = (Vec::new(), Vec::new());
-      for (k, v) in json.entries() {
-        keys.push(AST { variant: ASTVal::String(k.to_string()), location });
-        vals.push(ast_from_jsonvalue(v.clone(), location));
+// JSON is read here rather than by the json crate, which rounds integers past 18 digits.
+// an integer comes in exact however long it is, and a number with a fraction or an exponent as a float
+fn json_read(text: &str, i: &mut usize, location: Location) -> Option<AST> {
+  let b = text.as_bytes();
+  json_skip(b, i);
+  let variant = match *b.get(*i)? {
+    b'{' => {
+      *i += 1;
+      let mut es = Vec::new();
+      json_skip(b, i);
+      if b.get(*i) == Some(&b'}') {
+        *i += 1;
+      } else {
+        loop {
+          json_skip(b, i);
+          let k = json_read_string(text, i)?;
+          json_skip(b, i);
+          if b.get(*i) != Some(&b':') { return None; }
+          *i += 1;
+          // a repeated key keeps its first place and its last value
+          dict_insert(&mut es, k, json_read(text, i, location)?);
+          json_skip(b, i);
+          match *b.get(*i)? {
+            b',' => { *i += 1; },
+            b'}' => { *i += 1; break; },
+            _ => { return None; }
+          }
+        }
+      }
+      ASTVal::Dict(es)
+    },
+    b'[' => {
+      *i += 1;
+      let mut vs = Vec::new();
+      json_skip(b, i);
+      if b.get(*i) == Some(&b']') {
+        *i += 1;
+      } else {
+        loop {
+          vs.push(json_read(text, i, location)?);
+          json_skip(b, i);
+          match *b.get(*i)? {
+            b',' => { *i += 1; },
+            b']' => { *i += 1; break; },
+            _ => { return None; }
+          }
+        }
       }
+      ASTVal::Array(vs)
+    },
+    b'"' => ASTVal::String(json_read_string(text, i)?),
+    _ if text[*i..].starts_with("true") => { *i += 4; ASTVal::Bool(true) },
+    _ if text[*i..].starts_with("false") => { *i += 5; ASTVal::Bool(false) },
+    _ if text[*i..].starts_with("null") => { *i += 4; ASTVal::Null },
+    _ => ASTVal::Num(json_read_number(text, i)?)
+  };
+  Some(AST { variant, location })
+}
 
-      AST { variant: ASTVal::Array(Vec::from([AST { variant: ASTVal::Array(keys), location }, AST { variant: ASTVal::Array(vals), location }])), location }
+fn json_skip(b: &[u8], i: &mut usize) {
+  while b.get(*i).is_some_and(|c| matches!(c, b' ' | b'\t' | b'\n' | b'\r')) {
+    *i += 1;
+  }
+}
+
+fn json_read_number(text: &str, i: &mut usize) -> Option<Number> {
+  let b = text.as_bytes();
+  let digits = |i: &mut usize| {
+    let start = *i;
+    while b.get(*i).is_some_and(u8::is_ascii_digit) {
+      *i += 1;
     }
+    *i > start
+  };
+
+  let start = *i;
+  if b.get(*i) == Some(&b'-') { *i += 1; }
+  if b.get(*i) == Some(&b'0') {
+    *i += 1;
+  } else if !digits(i) {
+    return None;
   }
+  let whole = *i;
+  if b.get(*i) == Some(&b'.') {
+    *i += 1;
+    if !digits(i) { return None; }
+  }
+  if matches!(b.get(*i), Some(b'e' | b'E')) {
+    *i += 1;
+    if matches!(b.get(*i), Some(b'+' | b'-')) { *i += 1; }
+    if !digits(i) { return None; }
+  }
+
+  let lit = &text[start..*i];
+  if *i == whole { Number::parse(lit) } else { lit.parse::<f64>().ok().map(Number::Float) }
+}
+
+fn json_read_string(text: &str, i: &mut usize) -> Option<String> {
+  let b = text.as_bytes();
+  if b.get(*i) != Some(&b'"') { return None; }
+  *i += 1;
+
+  let mut out = String::new();
+  loop {
+    let start = *i;
+    while b.get(*i).is_some_and(|c| *c != b'"' && *c != b'\\' && *c >= b' ') {
+      *i += 1;
+    }
+    out += &text[start..*i];
+
+    match *b.get(*i)? {
+      b'"' => { *i += 1; return Some(out); },
+      b'\\' => {
+        *i += 2;
+        match *b.get(*i - 1)? {
+          b'"' => out.push('"'),
+          b'\\' => out.push('\\'),
+          b'/' => out.push('/'),
+          b'b' => out.push('\u{8}'),
+          b'f' => out.push('\u{c}'),
+          b'n' => out.push('\n'),
+          b'r' => out.push('\r'),
+          b't' => out.push('\t'),
+          b'u' => {
+            // a character past the basic plane is written as two escapes, a surrogate pair
+            let high = json_read_hex(text, i)?;
+            let low = if (0xD800..0xDC00).contains(&high) && text[*i..].starts_with("\\u") {
+              *i += 2;
+              Some(json_read_hex(text, i)?)
+            } else {
+              None
+            };
+            out.extend(char::decode_utf16(std::iter::once(high).chain(low)).map(|c| c.unwrap_or(char::REPLACEMENT_CHARACTER)));
+          },
+          _ => { return None; }
+        }
+      },
+      // control characters must be escaped
+      _ => { return None; }
+    }
+  }
+}
+
+fn json_read_hex(text: &str, i: &mut usize) -> Option<u16> {
+  let hex = text.get(*i..*i + 4).filter(|h| h.bytes().all(|c| c.is_ascii_hexdigit()))?;
+  *i += 4;
+  u16::from_str_radix(hex, 16).ok()
 }
 
-fn jsonvalue_from_ast<S: AsRef<str>>(ast: AST, s: S) -> Result<json::JsonValue, String> {
+// integers are written in full however long they are, and floats always with a point or an exponent,
+// so that each is read back as it was
+fn json_write<S: AsRef<str>>(ast: AST, s: S) -> Result<String, Error> {
   match ast.variant {
-    ASTVal::Num(i) => Ok(json::JsonValue::Number(i.into())),
-    ASTVal::String(s) | ASTVal::Symbol(s) | ASTVal::Ident(s) => Ok(json::JsonValue::String(s)),
-    ASTVal::Array(vs) => Ok(json::JsonValue::Array(vs.into_iter().map(|v| jsonvalue_from_ast(v, s.as_ref())).collect::<Result<Vec<json::JsonValue>, String>>()?)),
-    _ => Err(error(s, ast.location.col, ast.location.len, "Invalid AST to jsonify.", noteformat!("The given value was:\n\r{}", ast)))
+    ASTVal::Num(Number::Float(f)) => Ok(if f.is_finite() { format!("{:?}", f) } else { "null".to_string() }),
+    ASTVal::Num(n) => Ok(n.to_string()),
+    ASTVal::Bool(b) => Ok(b.to_string()),
+    ASTVal::Null => Ok("null".to_string()),
+    ASTVal::String(s) | ASTVal::Symbol(s) | ASTVal::Ident(s) => Ok(json::stringify(s)),
+    ASTVal::Array(vs) => Ok(format!("[{}]", vs.into_iter().map(|v| json_write(v, s.as_ref())).collect::<Result<Vec<String>, Error>>()?.join(","))),
+    ASTVal::Dict(es) => Ok(format!("{{{}}}", es.into_iter().map(|(k, v)| Ok(format!("{}:{}", json::stringify(k), json_write(v, s.as_ref())?))).collect::<Result<Vec<String>, Error>>()?.join(","))),
+    // an array of objects, one per row
+    ASTVal::Table(cs, rs) => json_write(AST { variant: ASTVal::Array(table_rows(&cs, rs, ast.location)), location: ast.location }, s),
+    _ => Err(error(ErrorKind::Type, s, ast.location, "Invalid AST to jsonify.", noteformat!("The given value was:\n\r{}", ast)))
   }
 }
 
-fn scalar_function<S: AsRef<str>>(name: S, larg: Option<AST>, rarg: Option<AST>, s: &String, loc: Location, env: &mut HashMap<String, AST>, fail_extern: bool, redr: Redirect) -> Result<AST, String> {
+fn scalar_function<S: AsRef<str>>(name: S, larg: Option<AST>, rarg: Option<AST>, s: &String, loc: Location, env: &mut Env, fail_extern: bool, redr: &Redirect) -> Result<AST, Error> {
   let name = name.as_ref();
   
   match name {
@@ -497,14 +1043,14 @@ fn scalar_function<S: AsRef<str>>(name: S, larg: Option<AST>, rarg: Option<AST>,
 
       match rarg.variant {
         ASTVal::Num(i) => Ok(AST { variant: ASTVal::String(i.to_string()), location: loc }),
-        ASTVal::String(sr) => Ok(AST { variant: ASTVal::Num(f32::from_str(sr.trim()).map_err(|_| error(s, loc.col, loc.len, "Invalid candidate for numeric parsing.", noteformat!("The following string was given:\n\r{}", sr)))?), location: loc }),
-        _ => Err(error(s, loc.col, loc.len, "Invalid candidate for numeric parsing.", noteformat!("The following value was given:\n\r{}", rarg)))
+        ASTVal::String(sr) => Ok(AST { variant: ASTVal::Num(Number::parse(sr.trim()).ok_or_else(|| error(ErrorKind::Value, s, loc, "Invalid candidate for numeric parsing.", noteformat!("The following string was given:\n\r{}", sr)))?), location: loc }),
+        _ => Err(error(ErrorKind::Type, s, loc, "Invalid candidate for numeric parsing.", noteformat!("The following value was given:\n\r{}", rarg)))
       }
     },
     
     _ if is_arithmetic(name) => {
       if let (true, Some(ASTVal::Num(r))) = ( larg.is_none() && name == "-", &rarg.as_ref().map(|x| &x.variant)) {
-        return Ok(AST { variant: ASTVal::Num(-r), location: loc });
+        return Ok(AST { variant: ASTVal::Num(r.neg()), location: loc });
       }
 
       if let (true, Some(ASTVal::String(r))) = ( larg.is_none() && name == "-", &rarg.as_ref().map(|x| &x.variant)) {
@@ -512,66 +1058,161 @@ fn scalar_function<S: AsRef<str>>(name: S, larg: Option<AST>, rarg: Option<AST>,
       }
 
       if let (true, None, Some(x)) = (name == "*", &larg, &rarg) {
-        return Ok(AST { variant: ASTVal::Num(!is_truthy(&x) as u8 as f32), location: loc });
+        return Ok(AST { variant: ASTVal::Num(Number::from(!is_truthy(x))), location: loc });
       }
       
       let (larg, rarg) = (inpipe_to_ast(unoptionize(larg), s, &loc, StdoutCaptureType::Data)?, inpipe_to_ast(unoptionize(rarg), s, &loc, StdoutCaptureType::Data)?);
       
       if let (ASTVal::Num(l), ASTVal::Num(r)) = (&larg.variant, &rarg.variant) {
-        Ok(AST { variant: ASTVal::Num(arithmetic(name, *l, *r)), location: loc })
+        Ok(AST { variant: ASTVal::Num(arithmetic(name, l, r)), location: loc })
       } else if let (true, ASTVal::String(s1), ASTVal::String(s2)) = (name == "+", &larg.variant, &rarg.variant) {
         Ok(AST { variant: ASTVal::String(s1.to_string() + s2.as_str()), location: loc })
       } else {
-        Err(error(s, loc.col, loc.len, format!("Cannot perform arithmetic {0} on mistyped value.", name), noteformat!("The left value was:\n\r{}\n\rAnd the right value was:\n\r{}", larg, rarg)))
+        Err(error(ErrorKind::Type, s, loc, format!("Cannot perform arithmetic {0} on mistyped value.", name), noteformat!("The left value was:\n\r{}\n\rAnd the right value was:\n\r{}", larg, rarg)))
       }
     },
 
     "cd" => {
       if fail_extern {
-        return Err("[cmd]".to_string());
+        return Err(preview());
       }
 
       let (_larg, rarg) = (inpipe_to_ast(unoptionize(larg), s, &loc, StdoutCaptureType::Data)?, inpipe_to_ast(unoptionize(rarg), s, &loc, StdoutCaptureType::Data)?);
       
+      let (dir, back) = match rarg.variant {
+        ASTVal::String(ref st) if st == "-" => (env::var("OLDPWD").map_err(|_| error(ErrorKind::Value, s, rarg.location, "No previous directory.", "`cd \"-\"` returns to the directory before the last change, but there has not been one."))?, true),
+        ASTVal::String(ref st) => (dirs::expand_tilde(st), false),
+        ASTVal::Array(ref vs) if vs.is_empty() => (dirs::home().ok_or_else(|| error(ErrorKind::Value, s, loc, "No home directory.", "$HOME is not set, and the password database has no entry for this user."))?, false),
+        _ => { return Err(error(ErrorKind::Type, s, rarg.location, "Expected string to indicate directory.", noteformat!("The given value was:\n\r{}", rarg))); }
+      };
+
+      match dirs::change(Path::new(&dir)) {
+        Err(e) => Err(error(ErrorKind::Io, s, loc, format!("Could not open directory — {}", e), "Try `ls` to list extant directories.")),
+        // returning shows where it went, as in other shells
+        Ok(()) if back => Ok(AST { variant: ASTVal::String(dir), location: loc }),
+        Ok(()) => Ok(AST { variant: ASTVal::Array(Vec::new()), location: loc })
+      }
+    },
+
+    "pushd" => {
+      if fail_extern {
+        return Err(preview());
+      }
+
+      let rarg = inpipe_to_ast(unoptionize(rarg), s, &loc, StdoutCaptureType::Data)?;
       match rarg.variant {
-        ASTVal::String(st) => {
-          let root = Path::new(&st);
-          if let Err(e) = env::set_current_dir(&root) {
-            Err(error(s, loc.col, loc.len, format!("Could not open directory — {}", e), "Try `ls` to list extant directories."))
-          } else {
-            Ok(AST { variant: ASTVal::Array(Vec::new()), location: loc })
-          }
+        ASTVal::String(ref st) => {
+          dirs::push(Path::new(&dirs::expand_tilde(st))).map_err(|e| error(ErrorKind::Io, s, loc, format!("Could not open directory — {}", e), "Try `ls` to list extant directories."))?;
+          Ok(dir_stack(loc))
         },
-        ASTVal::Array(vs) if vs.len() == 0 => {
-          let root = Path::new("..");
-          if let Err(e) = env::set_current_dir(&root) {
-            Err(error(s, loc.col, loc.len, format!("Could not open directory — {}", e), "This error occurred because a higher directory could not be opened."))
-          } else {
-            Ok(AST { variant: ASTVal::Array(Vec::new()), location: loc })
-          }
+        _ => Err(error(ErrorKind::Type, s, rarg.location, "Expected string to indicate directory.", noteformat!("The given value was:\n\r{}", rarg)))
+      }
+    },
+
+    "popd" => {
+      if fail_extern {
+        return Err(preview());
+      }
+
+      match dirs::pop() {
+        None => Err(error(ErrorKind::Value, s, loc, "The directory stack is empty.", "pushd saves a directory for popd to return to.")),
+        Some(Err(e)) => Err(error(ErrorKind::Io, s, loc, format!("Could not open directory — {}", e), "It stays on the stack.")),
+        Some(Ok(_)) => Ok(dir_stack(loc))
+      }
+    },
+
+    "dirs" => Ok(dir_stack(loc)),
+
+    "dict" => {
+      let rarg = inpipe_to_ast(unoptionize(rarg), s, &loc, StdoutCaptureType::Data)?;
+      let cannederr = || error(ErrorKind::Type, s, rarg.location, "Invalid arguments to dict.", noteformat!("dict expects pairs of string keys and values, or an array of keys beside an array of values.  It recieved:\n\r{}", rarg));
+
+      let pairs = match rarg.variant {
+        ASTVal::Dict(_) => { return Ok(rarg); },
+        // keys beside values, as json used to give objects
+        ASTVal::Array(ref vs) if vs.len() == 2 && matches!((&vs[0].variant, &vs[1].variant), (ASTVal::Array(ks), ASTVal::Array(xs)) if ks.len() == xs.len()) => match (&vs[0].variant, &vs[1].variant) {
+          (ASTVal::Array(ks), ASTVal::Array(xs)) => ks.iter().cloned().zip(xs.iter().cloned()).collect::<Vec<(AST, AST)>>(),
+          _ => unreachable!()
+        },
+        ASTVal::Array(ref vs) if vs.len() % 2 == 0 => vs.chunks(2).map(|pair| (pair[0].clone(), pair[1].clone())).collect(),
+        _ => { return Err(cannederr()); }
+      };
+
+      let mut es = Vec::new();
+      for (k, v) in pairs {
+        match k.variant {
+          ASTVal::String(k) => dict_insert(&mut es, k, v),
+          _ => { return Err(cannederr()); }
         }
-        _ => Err(error(s, rarg.location.col, rarg.location.len, "Expected string to indicate directory.", noteformat!("The given value was:\n\r{}", rarg)))
+      }
+      Ok(AST { variant: ASTVal::Dict(es), location: loc })
+    },
+
+    "keys" | "values" => {
+      let rarg = inpipe_to_ast(unoptionize(rarg), s, &loc, StdoutCaptureType::Data)?;
+      match rarg.variant {
+        ASTVal::Dict(es) => Ok(AST { variant: ASTVal::Array(es.into_iter().map(|(k, v)| if name == "keys" { AST { variant: ASTVal::String(k), location: loc } } else { v }).collect()), location: loc }),
+        // the column names, and the rows as dicts
+        ASTVal::Table(cs, rs) => Ok(AST { variant: ASTVal::Array(if name == "keys" {
+          cs.into_iter().map(|c| AST { variant: ASTVal::String(c), location: loc }).collect()
+        } else {
+          table_rows(&cs, rs, loc)
+        }), location: loc }),
+        _ => Err(error(ErrorKind::Type, s, rarg.location, format!("Invalid argument to {}.", name), noteformat!("{} expects a dict or table.  It recieved:\n\r{}", name, rarg)))
+      }
+    },
+
+    "table" => {
+      let rarg = inpipe_to_ast(unoptionize(rarg), s, &loc, StdoutCaptureType::Data)?;
+      let cannederr = || error(ErrorKind::Type, s, rarg.location, "Invalid arguments to table.", noteformat!("table expects an array of dicts as rows, an array of arrays headed by the column names, or a dict of columns of the same length.  It recieved:\n\r{}", rarg));
+
+      match rarg.variant {
+        ASTVal::Table(..) => Ok(rarg),
+        ASTVal::Array(ref vs) if vs.iter().all(|v| matches!(v.variant, ASTVal::Dict(_))) => Ok(table_from_dicts(vs.iter().map(|v| match v.variant {
+          ASTVal::Dict(ref es) => es.clone(),
+          _ => unreachable!()
+        }).collect(), loc)),
+        ASTVal::Array(ref vs) if vs.iter().all(|v| matches!(v.variant, ASTVal::Array(_))) => table_from_header(vs.iter().map(|v| match v.variant {
+          ASTVal::Array(ref r) => r.clone(),
+          _ => unreachable!()
+        }).collect(), s, loc),
+        ASTVal::Dict(ref es) => {
+          let mut cols = Vec::new();
+          for (_, v) in es {
+            match v.variant {
+              ASTVal::Array(ref c) if cols.first().is_none_or(|f: &Vec<AST>| f.len() == c.len()) => cols.push(c.clone()),
+              _ => { return Err(cannederr()); }
+            }
+          }
+          let len = cols.first().map_or(0, |c| c.len());
+          let rows = (0..len).map(|i| cols.iter().map(|c| c[i].clone()).collect()).collect();
+          Ok(AST { variant: ASTVal::Table(es.iter().map(|(k, _)| k.to_string()).collect(), rows), location: loc })
+        },
+        _ => Err(cannederr())
       }
     },
 
     "exit" => {
       if fail_extern {
-        return Err("[cmd]".to_string());
+        return Err(preview());
       }
 
       let (_larg, rarg) = (inpipe_to_ast(unoptionize(larg), s, &loc, StdoutCaptureType::Data)?, inpipe_to_ast(unoptionize(rarg), s, &loc, StdoutCaptureType::Data)?);
       
       match rarg.variant {
-        ASTVal::Num(n) if n.fract() == 0.0 => std::process::exit(n.round() as i32),
+        ASTVal::Num(n) if n.as_i64().is_some() => std::process::exit(n.as_i64().unwrap() as i32),
         ASTVal::Array(vs) if vs.len() == 0 => std::process::exit(0),
-        _ => Err(error(s, rarg.location.col, rarg.location.len, "Require number for exit code.", noteformat!("The given value was:\n\r{}", rarg)))
+        _ => Err(error(ErrorKind::Type, s, rarg.location, "Require number for exit code.", noteformat!("The given value was:\n\r{}", rarg)))
       }
     },
 
     "list" => {
-      let (_larg, rarg) = (inpipe_to_ast(unoptionize(larg), s, &loc, StdoutCaptureType::Data)?, inpipe_to_ast(unoptionize(rarg), s, &loc, StdoutCaptureType::Data)?);
+      let (larg, rarg) = (inpipe_to_ast(unoptionize(larg), s, &loc, StdoutCaptureType::Data)?, inpipe_to_ast(unoptionize(rarg), s, &loc, StdoutCaptureType::Data)?);
+      let header = matches!(larg.variant, ASTVal::Symbol(ref x) if x == "--header");
 
       match rarg.variant {
+        // the first line names the columns of a table
+        ASTVal::String(st) if header => table_from_header(st.lines().map(|l| l.split("\t").map(|e| table_field(e.to_string(), loc)).collect()).collect(), s, loc),
         ASTVal::String(s) => {
           let mut vs = s.split("\n").map(|e| AST { variant: 
               match e.split("\t").collect::<Vec<&str>>() {
@@ -586,10 +1227,11 @@ fn scalar_function<S: AsRef<str>>(name: S, larg: Option<AST>, rarg: Option<AST>,
           Ok(AST { variant: match vs { vs if vs.len() != 1 => ASTVal::Array(vs), mut vs => vs.remove(0).variant }, location: loc })
         },
         ASTVal::Array(vs) => Ok(AST { variant: ASTVal::String(vs.into_iter().map(|e| match e.variant {
-          ASTVal::Array(vs2) => vs2.into_iter().map(|e| ast_stringify(e, s, loc)).collect::<Result<Vec<String>, String>>().map(|x| x.join("\t")),
+          ASTVal::Array(vs2) => vs2.into_iter().map(|e| ast_stringify(e, s, loc)).collect::<Result<Vec<String>, Error>>().map(|x| x.join("\t")),
           _ => ast_stringify(e, s, loc)
-        }).collect::<Result<Vec<String>, String>>()?.join("\n")), location: loc }),
-        _ => Err(error(s, loc.col, loc.len, "Invalid argument to list.", "List either requires a string to translate to an array or an array to translate to a string."))
+        }).collect::<Result<Vec<String>, Error>>()?.join("\n")), location: loc }),
+        ASTVal::Table(cs, rs) => Ok(AST { variant: ASTVal::String(ast_serialize(table_to_arrays(cs, rs, loc), s, loc)?.trim_end_matches('\n').to_string()), location: loc }),
+        _ => Err(error(ErrorKind::Type, s, loc, "Invalid argument to list.", "List either requires a string to translate to an array or table, or an array or table to translate to a string."))
       }
     },
     "csv" => {
@@ -600,17 +1242,23 @@ fn scalar_function<S: AsRef<str>>(name: S, larg: Option<AST>, rarg: Option<AST>,
         _ => ','
       } as u8;
 
+      // a table is written with its header first
+      let rarg = match rarg.variant {
+        ASTVal::Table(cs, rs) => table_to_arrays(cs, rs, loc),
+        v => AST { variant: v, location: rarg.location }
+      };
+
       match rarg.variant {
         ASTVal::String(st) => {
           let mut rdr = csv::ReaderBuilder::new().flexible(true).delimiter(delimiter).from_reader(st.as_bytes());
           
-          let mut records = rdr.records().collect::<Result<Vec<csv::StringRecord>, csv::Error>>().map_err(|_e| error(s, loc.col, loc.len, "Failed to parse CSV.", noteformat!("The given value was:\n\r{}", st)))?;
+          let mut records = rdr.records().collect::<Result<Vec<csv::StringRecord>, csv::Error>>().map_err(|_e| error(ErrorKind::Value, s, loc, "Failed to parse CSV.", noteformat!("The given value was:\n\r{}", st)))?;
 
-          records.insert(0, rdr.headers().map_err(|_e| error(s, loc.col, loc.len, "Failed to parse CSV headers.", noteformat!("The given value was:\n\r{}", st)))?.clone());
+          records.insert(0, rdr.headers().map_err(|_e| error(ErrorKind::Value, s, loc, "Failed to parse CSV headers.", noteformat!("The given value was:\n\r{}", st)))?.clone());
           
-          let records = records.into_iter().map(|r| r.deserialize(None)).collect::<Result<Vec<Vec<String>>, csv::Error>>().map_err(|_e| error(s, loc.col, loc.len, "Failed to deserialize CSV object.", noteformat!("The given value was:\n\r{}", st)))?;
+          let records = records.into_iter().map(|r| r.deserialize(None)).collect::<Result<Vec<Vec<String>>, csv::Error>>().map_err(|_e| error(ErrorKind::Value, s, loc, "Failed to deserialize CSV object.", noteformat!("The given value was:\n\r{}", st)))?;
 
-          Ok(AST { variant: ASTVal::Array(records.into_iter().map(|r| AST { variant: ASTVal::Array(r.into_iter().map(|sss| AST { variant: ASTVal::String(sss), location: loc }).collect()), location: loc }).collect()), location: loc })
+          table_from_header(records.into_iter().map(|r| r.into_iter().map(|sss| table_field(sss, loc)).collect()).collect(), s, loc)
         },
         ASTVal::Array(vs) => {
           let mut wtr = csv::WriterBuilder::new().flexible(true).delimiter(delimiter).from_writer(Vec::new());
@@ -632,21 +1280,22 @@ fn scalar_function<S: AsRef<str>>(name: S, larg: Option<AST>, rarg: Option<AST>,
           }
           Ok(AST { variant: ASTVal::String(String::from_utf8(wtr.into_inner().map_err(|e| internalfailure(e, "unwrap CSV record object", s, &loc))?).map_err(|e| internalfailure(e, "convert CSV object to string", s, &loc))?), location: loc })
         },
-        _ => Err(error(s, loc.col, loc.len, "Invalid argument to csv.", "CSV either requires a string to translate to an array or an array to translate to a string."))
+        _ => Err(error(ErrorKind::Type, s, loc, "Invalid argument to csv.", "CSV either requires a string to translate to a table, or an array or table to translate to a string."))
       }
     },
     "json" => {
       let (_larg, rarg) = (inpipe_to_ast(unoptionize(larg), s, &loc, StdoutCaptureType::Data)?, inpipe_to_ast(unoptionize(rarg), s, &loc, StdoutCaptureType::Data)?);
       match rarg.variant {
         ASTVal::String(st) => {
-          let parsed = json::parse(&st).map_err(|_e| error(s, loc.col, loc.len, "Failed to parse JSON.", noteformat!("Invalid JSON:\n\r{}", st)))?;
-          Ok(ast_from_jsonvalue(parsed, loc))
+          let mut i = 0;
+          let parsed = json_read(&st, &mut i, loc);
+          json_skip(st.as_bytes(), &mut i);
+          parsed.filter(|_| i == st.len()).ok_or_else(|| error(ErrorKind::Value, s, loc, "Failed to parse JSON.", noteformat!("Invalid JSON:\n\r{}", st)))
         },
-        ASTVal::Array(_) => {
-          let converted = jsonvalue_from_ast(rarg, s)?;
-          Ok(AST { variant: ASTVal::String(json::stringify(converted)), location: loc })
+        ASTVal::Array(_) | ASTVal::Dict(_) | ASTVal::Table(..) => {
+          Ok(AST { variant: ASTVal::String(json_write(rarg, s)?), location: loc })
         },
-        _ => Err(error(s, loc.col, loc.len, "Invalid argument to json.", "JSON either requires a string to translate to an array or an array to translate to a string."))
+        _ => Err(error(ErrorKind::Type, s, loc, "Invalid argument to json.", "JSON either requires a string to translate to data, or an array, dict or table to translate to a string."))
       }
     },
 
@@ -661,8 +1310,8 @@ fn scalar_function<S: AsRef<str>>(name: S, larg: Option<AST>, rarg: Option<AST>,
         let (larg, rarg) = (inpipe_to_ast(unoptionize(larg), s, &loc, StdoutCaptureType::Data)?, inpipe_to_ast(unoptionize(rarg), s, &loc, StdoutCaptureType::Data)?);
 
         let rotate = match larg.variant {
-          ASTVal::Num(n) if n.fract() == 0.0 => Ok(n as i32),
-          _ => Err(error(s, loc.col, loc.len, "Invalid rotation degree.", noteformat!("Rotate requires an integral argument to rotate by, instead found:\n\r{}", larg)))
+          ASTVal::Num(n) if n.as_i64().is_some() => Ok(n.as_i64().unwrap() as i32),
+          _ => Err(error(ErrorKind::Type, s, loc, "Invalid rotation degree.", noteformat!("Rotate requires an integral argument to rotate by, instead found:\n\r{}", larg)))
         }?;
 
         let elems = match rarg.variant {
@@ -686,17 +1335,17 @@ fn scalar_function<S: AsRef<str>>(name: S, larg: Option<AST>, rarg: Option<AST>,
       }
     },
     x if x == CHARSET::Take => {
-      let (larg, rarg) = (match larg { Some(larg) => inpipe_to_ast(larg, s, &loc, StdoutCaptureType::Data)?, None => AST { variant: ASTVal::Num(1.0), location: loc } }, inpipe_to_ast(unoptionize(rarg), s, &loc, StdoutCaptureType::Data)?);
+      let (larg, rarg) = (match larg { Some(larg) => inpipe_to_ast(larg, s, &loc, StdoutCaptureType::Data)?, None => AST { variant: ASTVal::Num(Number::from(1)), location: loc } }, inpipe_to_ast(unoptionize(rarg), s, &loc, StdoutCaptureType::Data)?);
 
       let takenum = match larg.variant {
-        ASTVal::Num(i) if i.fract() == 0.0 => Ok(i as i32),
-        _ => Err(error(s, loc.col, loc.len, "Invalid number to take.", noteformat!("Expected integral count to take, instead found:\n\r{}", larg)))
+        ASTVal::Num(i) if i.as_i64().is_some() => Ok(i.as_i64().unwrap() as i32),
+        _ => Err(error(ErrorKind::Type, s, loc, "Invalid number to take.", noteformat!("Expected integral count to take, instead found:\n\r{}", larg)))
       }?;
 
       match rarg.variant {
         ASTVal::Array(mut vs) => {
           if takenum.abs() as usize > vs.len() {
-            return Err(error(s, loc.col, loc.len, "Index out of bounds error.", noteformat!("The given count to take is greater than the length of the array.  The count was:\n\r{}\n\rBut the array was:\n\r{}", takenum, AST { variant: ASTVal::Array(vs), location: loc })));
+            return Err(error(ErrorKind::Index, s, loc, "Index out of bounds error.", noteformat!("The given count to take is greater than the length of the array.  The count was:\n\r{}\n\rBut the array was:\n\r{}", takenum, AST { variant: ASTVal::Array(vs), location: loc })));
           }
           if takenum >= 0 {
             let takenum = takenum as usize;
@@ -707,6 +1356,12 @@ fn scalar_function<S: AsRef<str>>(name: S, larg: Option<AST>, rarg: Option<AST>,
             Ok(AST { variant: ASTVal::Array(vs.drain(s..e).collect()), location: loc })
           }
         },
+        // the first or last rows of a table, as many as there are
+        ASTVal::Table(cs, mut rs) => {
+          let n = std::cmp::min(takenum.unsigned_abs() as usize, rs.len());
+          let rs = if takenum >= 0 { rs.drain(0..n).collect() } else { rs.split_off(rs.len() - n) };
+          Ok(AST { variant: ASTVal::Table(cs, rs), location: loc })
+        },
         _ => Ok(rarg)
       }
     },
@@ -720,9 +1375,9 @@ fn scalar_function<S: AsRef<str>>(name: S, larg: Option<AST>, rarg: Option<AST>,
 
       let ls = match ls {
         Some(ls) => Some(ls.into_iter().map(|l| match l.variant {
-        ASTVal::Num(x) if x >= 0.0 && x.fract() == 0.0 => Ok(x as u32 as usize),
-        _ => Err(error(s, l.location.col, l.location.len, "Invalid axis specifier.", noteformat!("Expected an integral positive numeric argument, but instead found:\n\r{}", l)))
-      }).collect::<Result<Vec<usize>, String>>()?),
+        ASTVal::Num(x) if x.as_i64().is_some_and(|x| x >= 0) => Ok(x.as_i64().unwrap() as usize),
+        _ => Err(error(ErrorKind::Type, s, l.location, "Invalid axis specifier.", noteformat!("Expected an integral positive numeric argument, but instead found:\n\r{}", l)))
+      }).collect::<Result<Vec<usize>, Error>>()?),
         None => None
       };
 
@@ -760,6 +1415,26 @@ fn scalar_function<S: AsRef<str>>(name: S, larg: Option<AST>, rarg: Option<AST>,
         None => {
           Ok(AST { variant: ASTVal::Array(Vec::from([rarg])), location: loc })
         },
+        // the right dict's values win, and its new keys go after the left's
+        Some(AST { variant: ASTVal::Dict(mut les), .. }) if matches!(rarg.variant, ASTVal::Dict(_)) => {
+          if let ASTVal::Dict(res) = rarg.variant {
+            for (k, v) in res {
+              dict_insert(&mut les, k, v);
+            }
+          }
+          Ok(AST { variant: ASTVal::Dict(les), location: loc })
+        },
+        // the rows of both, under the columns of either
+        Some(AST { variant: ASTVal::Table(lcs, lrs), .. }) if matches!(rarg.variant, ASTVal::Table(..)) => {
+          let mut rows = table_rows(&lcs, lrs, loc);
+          if let ASTVal::Table(rcs, rrs) = rarg.variant {
+            rows.extend(table_rows(&rcs, rrs, loc));
+          }
+          Ok(table_from_dicts(rows.into_iter().map(|r| match r.variant {
+            ASTVal::Dict(es) => es,
+            _ => unreachable!()
+          }).collect(), loc))
+        },
         Some(larg) => {
           let mut larg = match arrayifyast(larg).variant {
             ASTVal::Array(vs) => vs,
@@ -779,9 +1454,9 @@ fn scalar_function<S: AsRef<str>>(name: S, larg: Option<AST>, rarg: Option<AST>,
       if larg.is_some() {
         let (larg, rarg) = (inpipe_to_ast(unoptionize(larg), s, &loc, StdoutCaptureType::Data)?, inpipe_to_ast(unoptionize(rarg), s, &loc, StdoutCaptureType::Data)?);
         
-        Ok(AST { variant: ASTVal::Num((compare(&larg, &rarg) == std::cmp::Ordering::Less) as u32 as f32), location: loc })
+        Ok(AST { variant: ASTVal::Num(Number::from(compare(&larg, &rarg) == std::cmp::Ordering::Less)), location: loc })
       } else {
-        let (_larg, rarg) = (inpipe_to_ast(unoptionize(larg), s, &loc, StdoutCaptureType::Data)?, arrayifyast(inpipe_to_ast(unoptionize(rarg), s, &loc, StdoutCaptureType::Data)?));
+        let (_larg, rarg) = (inpipe_to_ast(unoptionize(larg), s, &loc, StdoutCaptureType::Data)?, arrayifyast(table_as_rows(inpipe_to_ast(unoptionize(rarg), s, &loc, StdoutCaptureType::Data)?)));
 
         let vs = match rarg.variant {
           ASTVal::Array(vs) => vs,
@@ -790,16 +1465,16 @@ fn scalar_function<S: AsRef<str>>(name: S, larg: Option<AST>, rarg: Option<AST>,
         let mut is: Vec<usize> = (0..vs.len()).collect();
         is.sort_by(|a, b| compare(&vs[*a], &vs[*b]));
         
-        Ok(AST { variant: ASTVal::Array(is.into_iter().map(|i| AST { variant: ASTVal::Num(i as f32), location: loc }).collect()), location: loc })
+        Ok(AST { variant: ASTVal::Array(is.into_iter().map(|i| AST { variant: ASTVal::Num(Number::from(i as i64)), location: loc }).collect()), location: loc })
       }
     },
     x if x == CHARSET::Less => {
       if larg.is_some() {
         let (larg, rarg) = (inpipe_to_ast(unoptionize(larg), s, &loc, StdoutCaptureType::Data)?, inpipe_to_ast(unoptionize(rarg), s, &loc, StdoutCaptureType::Data)?);
         
-        Ok(AST { variant: ASTVal::Num((compare(&larg, &rarg) == std::cmp::Ordering::Greater) as u32 as f32), location: loc })
+        Ok(AST { variant: ASTVal::Num(Number::from(compare(&larg, &rarg) == std::cmp::Ordering::Greater)), location: loc })
       } else {
-        let (_larg, rarg) = (inpipe_to_ast(unoptionize(larg), s, &loc, StdoutCaptureType::Data)?, arrayifyast(inpipe_to_ast(unoptionize(rarg), s, &loc, StdoutCaptureType::Data)?));
+        let (_larg, rarg) = (inpipe_to_ast(unoptionize(larg), s, &loc, StdoutCaptureType::Data)?, arrayifyast(table_as_rows(inpipe_to_ast(unoptionize(rarg), s, &loc, StdoutCaptureType::Data)?)));
 
         let vs = match rarg.variant {
           ASTVal::Array(vs) => vs,
@@ -808,7 +1483,7 @@ fn scalar_function<S: AsRef<str>>(name: S, larg: Option<AST>, rarg: Option<AST>,
         let mut is: Vec<usize> = (0..vs.len()).collect();
         is.sort_by(|a, b| compare(&vs[*a], &vs[*b]).reverse());
         
-        Ok(AST { variant: ASTVal::Array(is.into_iter().map(|i| AST { variant: ASTVal::Num(i as f32), location: loc }).collect()), location: loc })
+        Ok(AST { variant: ASTVal::Array(is.into_iter().map(|i| AST { variant: ASTVal::Num(Number::from(i as i64)), location: loc }).collect()), location: loc })
       }
     },
     
@@ -816,22 +1491,61 @@ fn scalar_function<S: AsRef<str>>(name: S, larg: Option<AST>, rarg: Option<AST>,
       let (larg, rarg) = (inpipe_to_ast(unoptionize(larg), s, &loc, StdoutCaptureType::Data)?, inpipe_to_ast(unoptionize(rarg), s, &loc, StdoutCaptureType::Data)?);
       
       match rarg.variant {
+        ASTVal::Dict(ref es) => match larg.variant {
+          ASTVal::String(ref k) => dict_get(es, k).cloned().ok_or_else(|| error(ErrorKind::Index, s, loc, "Key not found.", noteformat!("The key {} is not in:\n\r{}", k, rarg))),
+          _ => Err(error(ErrorKind::Type, s, loc, "Expected string key.", noteformat!("The key supplied was:\n\r{}", larg)))
+        },
+        // a column by name or a row by number; several of either make a smaller table
+        ASTVal::Table(cs, mut rs) => {
+          let table = AST { variant: ASTVal::Table(cs.clone(), rs.clone()), location: rarg.location };
+          let column = |k: &str| cs.iter().position(|c| c == k).ok_or_else(|| error(ErrorKind::Index, s, loc, "Column not found.", noteformat!("The column {} is not in:\n\r{}", k, table)));
+          let row = |i: &Number| match i.as_i64().filter(|i| *i >= 0).map(|i| i as usize) {
+            Some(i) if i < rs.len() => Ok(i),
+            Some(i) => Err(error(ErrorKind::Index, s, loc, "Index out of bounds.", noteformat!("The row {} is out of bounds of:\n\r{}", i, table))),
+            None => Err(error(ErrorKind::Index, s, loc, "Index must be a nonnegative integer.", noteformat!("The index supplied was: {}", i)))
+          };
+
+          match larg.variant {
+            ASTVal::String(ref k) => {
+              let c = column(k)?;
+              Ok(AST { variant: ASTVal::Array(rs.into_iter().map(|mut r| r.swap_remove(c)).collect()), location: loc })
+            },
+            ASTVal::Num(ref i) => {
+              let i = row(i)?;
+              Ok(table_rows(&cs, Vec::from([rs.swap_remove(i)]), loc).remove(0))
+            },
+            ASTVal::Array(ref ks) if ks.iter().all(|k| matches!(k.variant, ASTVal::String(_))) => {
+              let is = ks.iter().map(|k| match k.variant {
+                ASTVal::String(ref k) => column(k),
+                _ => unreachable!()
+              }).collect::<Result<Vec<usize>, Error>>()?;
+              Ok(AST { variant: ASTVal::Table(is.iter().map(|i| cs[*i].clone()).collect(), rs.iter().map(|r| is.iter().map(|i| r[*i].clone()).collect()).collect()), location: loc })
+            },
+            ASTVal::Array(ref is) if is.iter().all(|i| matches!(i.variant, ASTVal::Num(_))) => {
+              let is = is.iter().map(|i| match i.variant {
+                ASTVal::Num(ref i) => row(i),
+                _ => unreachable!()
+              }).collect::<Result<Vec<usize>, Error>>()?;
+              Ok(AST { variant: ASTVal::Table(cs.clone(), is.into_iter().map(|i| rs[i].clone()).collect()), location: loc })
+            },
+            _ => Err(error(ErrorKind::Type, s, loc, "Expected column names or row numbers.", noteformat!("The index supplied was:\n\r{}", larg)))
+          }
+        },
         ASTVal::Array(ref vs) => {
           match larg.variant {
-            ASTVal::Num(i) => if i >= 0.0 && i.fract() == 0.0 {
-              let iusize = i as usize;
+            ASTVal::Num(ref i) => if let Some(iusize) = i.as_i64().filter(|i| *i >= 0).map(|i| i as usize) {
               if vs.len() > iusize {
                 Ok(vs[iusize].clone())
               } else {
-                Err(error(s, loc.col, loc.len, "Index out of bounds.", noteformat!("The index {} is out of bounds of:\n\r{}", iusize, rarg)))
+                Err(error(ErrorKind::Index, s, loc, "Index out of bounds.", noteformat!("The index {} is out of bounds of:\n\r{}", iusize, rarg)))
               }
             } else {
-              Err(error(s, loc.col, loc.len, "Index must be a nonnegative integer.", noteformat!("The index supplied was: {}", larg)))
+              Err(error(ErrorKind::Index, s, loc, "Index must be a nonnegative integer.", noteformat!("The index supplied was: {}", larg)))
             },
-            _ => Err(error(s, loc.col, loc.len, "Expected numeric index.", noteformat!("The index supplied was:\n\r{}", larg)))
+            _ => Err(error(ErrorKind::Type, s, loc, "Expected numeric index.", noteformat!("The index supplied was:\n\r{}", larg)))
           }
         },
-        _ => Err(error(s, loc.col, loc.len, "Expected array to index into.", noteformat!("The value supplied was instead:\n\r{}", rarg)))
+        _ => Err(error(ErrorKind::Type, s, loc, "Expected array to index into.", noteformat!("The value supplied was instead:\n\r{}", rarg)))
       }
     },
 
@@ -839,7 +1553,7 @@ fn scalar_function<S: AsRef<str>>(name: S, larg: Option<AST>, rarg: Option<AST>,
       if larg.is_none() {
         let rarg = inpipe_to_ast(unoptionize(rarg), s, &loc, StdoutCaptureType::Data)?;
         
-        Ok(AST { variant: ASTVal::Array(shapeof(&rarg).into_iter().map(|n| AST { variant: ASTVal::Num(n as u32 as i32 as f32), location: loc }).collect()), location: loc })
+        Ok(AST { variant: ASTVal::Array(shapeof(&rarg).into_iter().map(|n| AST { variant: ASTVal::Num(Number::from(n as i64)), location: loc }).collect()), location: loc })
       } else {
         let (larg, rarg) = (inpipe_to_ast(unoptionize(larg), s, &loc, StdoutCaptureType::Data)?, inpipe_to_ast(unoptionize(rarg), s, &loc, StdoutCaptureType::Data)?);
 
@@ -849,13 +1563,13 @@ fn scalar_function<S: AsRef<str>>(name: S, larg: Option<AST>, rarg: Option<AST>,
         };
 
         let mut vs = vs.into_iter().map(|v| match v.variant {
-          ASTVal::Num(i) if i.fract() == 0.0 => Ok(i as i32),
-          _ => Err("bad num".to_string())
-        }).collect::<Result<Vec<i32>, String>>()?;
+          ASTVal::Num(i) if i.as_i64().is_some() => Ok(i.as_i64().unwrap() as i32),
+          _ => Err(error(ErrorKind::Type, s, loc, "Invalid shape.", noteformat!("Reshape requires integral axis lengths, instead found:\n\r{}", v)))
+        }).collect::<Result<Vec<i32>, Error>>()?;
 
         let mut rs = match arrayifyast(rarg).variant {
           ASTVal::Array(vs) => if vs.len() < 1 {
-            Vec::from([AST { variant: ASTVal::Num(0.0), location: loc }])
+            Vec::from([AST { variant: ASTVal::Num(Number::from(0)), location: loc }])
           } else {
             vs 
           },
@@ -870,7 +1584,7 @@ fn scalar_function<S: AsRef<str>>(name: S, larg: Option<AST>, rarg: Option<AST>,
     x if x == CHARSET::Equal => {
       let (larg, rarg) = (inpipe_to_ast(unoptionize(larg), s, &loc, StdoutCaptureType::Data)?, inpipe_to_ast(unoptionize(rarg), s, &loc, StdoutCaptureType::Data)?);
 
-      Ok(AST { variant: ASTVal::Num(equality(&larg, &rarg) as u8 as f32), location: loc })
+      Ok(AST { variant: ASTVal::Num(Number::from(equality(&larg, &rarg))), location: loc })
     },
 
     x if x == CHARSET::Iota => {
@@ -902,13 +1616,13 @@ fn scalar_function<S: AsRef<str>>(name: S, larg: Option<AST>, rarg: Option<AST>,
       let mut result = Vec::new();
       match rarg.variant {
         ASTVal::Num(i) => {
-          let mut n = 0.0;
+          let mut n = Number::from(0);
           while n < i {
-            result.push(AST { variant: ASTVal::Num(n), location: loc });
-            n += 1.0;
+            result.push(AST { variant: ASTVal::Num(n.clone()), location: loc });
+            n = n.add(&Number::from(1));
           }
         },
-        _ => Err(error(s, rarg.location.col, rarg.location.len, "Invalid argument to iota.", noteformat!("Iota expects a numeric argument for sequence length, but instead found:\n\r{}", rarg)))?
+        _ => Err(error(ErrorKind::Type, s, rarg.location, "Invalid argument to iota.", noteformat!("Iota expects a numeric argument for sequence length, but instead found:\n\r{}", rarg)))?
       }
 
       Ok(AST { variant: ASTVal::Array(result), location: loc })
@@ -920,17 +1634,146 @@ fn scalar_function<S: AsRef<str>>(name: S, larg: Option<AST>, rarg: Option<AST>,
       Ok(rarg)
     },
 
+    "bg" => {
+      if fail_extern {
+        return Err(preview());
+      }
+
+      let rarg = unoptionize(rarg);
+      match rarg.variant {
+        ASTVal::Command(..) => {
+          let (line, cmd) = (s.clone(), detach_stdin(rarg));
+          let id = jobs::spawn(line.clone(), move || inpipe_to_ast(cmd, &line, &loc, StdoutCaptureType::None));
+          Ok(AST { variant: ASTVal::Job(id), location: loc })
+        },
+        ASTVal::Job(id) => if jobs::resume(id) {
+          Ok(rarg)
+        } else {
+          Err(error(ErrorKind::Job, s, rarg.location, "No such job.", noteformat!("There is no job {} in the job table.", id)))
+        },
+        ASTVal::Array(ref vs) if vs.is_empty() => match jobs::latest() {
+          Some(id) => { jobs::resume(id); Ok(AST { variant: ASTVal::Job(id), location: loc }) },
+          None => Err(error(ErrorKind::Job, s, loc, "No jobs to resume.", "Start a background job with `bg`, as in `sleep 5 | bg`."))
+        },
+        _ => Err(error(ErrorKind::Type, s, rarg.location, "Invalid argument to bg.", noteformat!("bg expects a process to run in the background or a job to resume.  It recieved:\n\r{}", rarg)))
+      }
+    },
+
+    "fg" => {
+      if fail_extern {
+        return Err(preview());
+      }
+
+      let rarg = unoptionize(rarg);
+      let id = match rarg.variant {
+        ASTVal::Job(id) => id,
+        ASTVal::Array(ref vs) if vs.is_empty() => jobs::latest().ok_or_else(|| error(ErrorKind::Job, s, loc, "No jobs to bring to the foreground.", "Start a background job with `bg`, as in `sleep 5 | bg`."))?,
+        _ => { return Err(error(ErrorKind::Type, s, rarg.location, "Invalid argument to fg.", noteformat!("fg expects a job.  It recieved:\n\r{}", rarg))); }
+      };
+
+      match jobs::foreground(id) {
+        Some(true) => job_result(id, s, loc)?.ok_or_else(|| error(ErrorKind::Job, s, loc, "Job stopped.", noteformat!("Job {} stopped again before it finished.", id))),
+        // stopped again, as by Ctrl-Z; it stays in the table for fg or bg
+        Some(false) => Ok(AST { variant: ASTVal::Job(id), location: loc }),
+        None => Err(error(ErrorKind::Job, s, rarg.location, "No such job.", noteformat!("There is no job {} in the job table.", id)))
+      }
+    },
+
+    "wait" => {
+      if fail_extern {
+        return Err(preview());
+      }
+
+      let rarg = unoptionize(rarg);
+      match rarg.variant {
+        ASTVal::Job(id) => job_result(id, s, loc)?.ok_or_else(|| error(ErrorKind::Job, s, loc, "Job is stopped.", noteformat!("Job {} is stopped, and would never finish.  Continue it with fg or bg first.", id))),
+        // stopped jobs are left out, and left in the table
+        ASTVal::Array(ref vs) if vs.is_empty() => Ok(AST { variant: ASTVal::Array(
+          jobs::list().into_iter().map(|(id, ..)| job_result(id, s, loc)).filter_map(Result::transpose).collect::<Result<Vec<AST>, Error>>()?
+        ), location: loc }),
+        _ => Err(error(ErrorKind::Type, s, rarg.location, "Invalid argument to wait.", noteformat!("wait expects a job.  It recieved:\n\r{}", rarg)))
+      }
+    },
+
+    "try" => {
+      let rarg = unoptionize(rarg);
+      // names are resolved here rather than with the rest of the line, so that a missing one is caught too
+      let res = resolve(rarg, env, s)
+        .and_then(|r| eval_command(r, s, env, fail_extern))
+        .and_then(|r| try_to_ast(r, s, loc));
+      // whatever failed inside was handled here
+      if !fail_extern {
+        reset_status();
+      }
+
+      match res {
+        Err(e) if e.kind == ErrorKind::Preview => Err(e),
+        Err(e) => {
+          let errval = ast_from_error(&e, loc);
+          match larg {
+            None => Ok(errval),
+            Some(handler) => {
+              let handler = resolve(handler, env, s)?;
+              if is_fn(&handler, env) {
+                call_function(None, handler, Some(errval), s, fail_extern, redr, env)
+              } else {
+                let handler = eval_command(handler, s, env, fail_extern)?;
+                inpipe_to_ast(handler, s, &loc, StdoutCaptureType::Data)
+              }
+            }
+          }
+        },
+        ok => ok
+      }
+    },
+
+    "if" => {
+      // a process as the condition holds when it succeeds, as in other shells
+      let cond = eval_command(unoptionize(larg), s, env, fail_extern)?;
+      let holds = match cond.variant {
+        ASTVal::Command(..) => {
+          let code = inpipe_to_ast(cond, s, &loc, StdoutCaptureType::None)?;
+          // a failing condition is not a failure of the line
+          if !fail_extern {
+            reset_status();
+          }
+          matches!(code.variant, ASTVal::Num(n) if n.is_zero())
+        },
+        _ => is_truthy(&inpipe_to_ast(cond, s, &loc, StdoutCaptureType::Data)?)
+      };
+
+      // only the chosen branch is evaluated; a process in it is left for the caller to run
+      let rarg = unoptionize(rarg);
+      let branch = match rarg.variant {
+        ASTVal::Apply(Some(then), f, Some(otherwise)) if matches!(&f.variant, ASTVal::Ident(x) if x == "else") => if holds { *then } else { *otherwise },
+        _ => if holds { rarg } else { nilarr() }
+      };
+      eval_command(branch, s, env, fail_extern)
+    },
+
+    "else" => Err(error(ErrorKind::Syntax, s, loc, "else outside of if.", "else separates the two branches of an if, as in `c if (a else b)`, and means nothing on its own.")),
+
+    "jobs" => {
+      Ok(AST { variant: ASTVal::Array(jobs::list().into_iter().map(|(id, state, line)| AST { variant: ASTVal::Array(Vec::from([
+        AST { variant: ASTVal::Job(id), location: loc },
+        AST { variant: ASTVal::String(state.to_string()), location: loc },
+        AST { variant: ASTVal::String(line.trim().to_string()), location: loc }
+      ])), location: loc }).collect()), location: loc })
+    },
+
     "pipe" => {
       let rarg = inpipe_to_ast(unoptionize(rarg), s, &loc, StdoutCaptureType::Data)?;
       
       let mut redir = Redirect { 
         stdout: ToRedirect::ToStdout,
-        stderr: ToRedirect::ToStderr
+        stderr: ToRedirect::ToStderr,
+        stdin: None,
+        env: Vec::new()
       };
 
       let mut is_unchanged = (true, true);
 
-      let cannederr = Err(error(s, rarg.location.col, rarg.location.len, "Invalid arguments to ].", noteformat!("] expects pairs of symbols indicating rerouting.  It recieved:\n\r{}", rarg)));
+      let cannederr = Err(error(ErrorKind::Type, s, rarg.location, "Invalid arguments to ].", noteformat!("] expects pairs of symbols or file names indicating rerouting.  It recieved:\n\r{}", rarg)));
       
       let arrai = arrayifyast(rarg);
       match arrai.variant {
@@ -962,30 +1805,28 @@ fn scalar_function<S: AsRef<str>>(name: S, larg: Option<AST>, rarg: Option<AST>,
             }
           }
           
-          if vs.len() % 2 != 0 {
-            return cannederr;
-          }
-
-          for pair in vs.chunks(2) {
-            let lsym = match &pair[0].variant {
-              ASTVal::Symbol(s) => s,
-              _ => { return cannederr; }
-            };
-            let rsym = match &pair[1].variant {
+          let mut vs = vs.into_iter();
+          while let Some(from) = vs.next() {
+            let lsym = match from.variant {
               ASTVal::Symbol(s) => s,
               _ => { return cannederr; }
             };
 
-            let togo = match rsym.as_str() {
-              "-o" => ToRedirect::ToStdout,
-              "-e" => ToRedirect::ToStderr,
-              "-n" => ToRedirect::ToNull,
+            let togo = match vs.next().map(|x| x.variant) {
+              Some(ASTVal::Symbol(x)) if x == "-o" => ToRedirect::ToStdout,
+              Some(ASTVal::Symbol(x)) if x == "-e" => ToRedirect::ToStderr,
+              Some(ASTVal::Symbol(x)) if x == "-n" => ToRedirect::ToNull,
+              Some(ASTVal::Symbol(x)) if x == "-a" => match vs.next().map(|x| x.variant) {
+                Some(ASTVal::String(f)) => ToRedirect::Append(dirs::expand_tilde(&f)),
+                _ => { return cannederr; }
+              },
+              Some(ASTVal::String(f)) => ToRedirect::File(dirs::expand_tilde(&f)),
               _ => { return cannederr; }
             };
             
             match lsym.as_str() {
               "-o" => {
-                if is_unchanged.0 || redir.stdout == ToRedirect::ToNull {
+                if is_unchanged.0 || redir.stdout == ToRedirect::ToNull || is_file_redirect(&togo) || is_file_redirect(&redir.stdout) {
                   redir.stdout = togo;
                 } else {
                   if togo != ToRedirect::ToNull && togo != redir.stdout {
@@ -996,7 +1837,7 @@ fn scalar_function<S: AsRef<str>>(name: S, larg: Option<AST>, rarg: Option<AST>,
                 is_unchanged.0 = false;
               },
               "-e" => {
-                if is_unchanged.1 || redir.stderr == ToRedirect::ToNull {
+                if is_unchanged.1 || redir.stderr == ToRedirect::ToNull || is_file_redirect(&togo) || is_file_redirect(&redir.stderr) {
                   redir.stderr = togo;
                 } else {
                   if togo != ToRedirect::ToNull && togo != redir.stderr {
@@ -1005,7 +1846,11 @@ fn scalar_function<S: AsRef<str>>(name: S, larg: Option<AST>, rarg: Option<AST>,
                 }
 
                 is_unchanged.1 = false;
-              }
+              },
+              "-i" => match togo {
+                ToRedirect::File(f) => { redir.stdin = Some(f); },
+                _ => { return cannederr; }
+              },
               _ => { return cannederr; }
             };
           }
@@ -1015,14 +1860,172 @@ fn scalar_function<S: AsRef<str>>(name: S, larg: Option<AST>, rarg: Option<AST>,
 
       let larg = eval_command(unoptionize(larg), s, env, fail_extern)?;
       match larg.variant {
-        ASTVal::Command(c, ags, si, _r) => Ok(AST { variant: ASTVal::Command(c, ags, si, redir), location: larg.location }),
+        ASTVal::Command(c, ags, si, r) => Ok(AST { variant: ASTVal::Command(c, ags, si, Redirect { env: r.env, ..redir }), location: larg.location }),
         _ => Ok(larg)
       }
-    }
+    },
+
+    "withenv" => {
+      let rarg = inpipe_to_ast(unoptionize(rarg), s, &loc, StdoutCaptureType::Data)?;
+      let cannederr = || error(ErrorKind::Type, s, rarg.location, "Invalid arguments to withenv.", noteformat!("withenv expects pairs of variable names and values.  It recieved:\n\r{}", rarg));
+
+      let mut vars = Vec::new();
+      match arrayifyast(rarg.clone()).variant {
+        ASTVal::Array(vs) if vs.len() % 2 == 0 => {
+          for pair in vs.chunks(2) {
+            match (&pair[0].variant, env_value(&pair[1])) {
+              (ASTVal::String(k), Some(v)) => vars.push((k.to_string(), v)),
+              _ => { return Err(cannederr()); }
+            }
+          }
+        },
+        _ => { return Err(cannederr()); }
+      };
+
+      let larg = eval_command(unoptionize(larg), s, env, fail_extern)?;
+      match larg.variant {
+        ASTVal::Command(c, ags, si, mut r) => {
+          r.env.extend(vars);
+          Ok(AST { variant: ASTVal::Command(c, ags, si, r), location: larg.location })
+        },
+        _ => Ok(larg)
+      }
+    },
+
+    "glob" => {
+      // `**` may walk a whole tree
+      if fail_extern {
+        return Err(preview());
+      }
+
+      let strict = match larg {
+        None => false,
+        Some(AST { variant: ASTVal::Symbol(ref x), .. }) if x == "--strict" => true,
+        Some(l) => { return Err(error(ErrorKind::Type, s, l.location, "Invalid option to glob.", noteformat!("glob takes --strict or nothing on its left.  It recieved:\n\r{}", l))); }
+      };
+
+      let rarg = unoptionize(rarg);
+      let patterns = match arrayifyast(rarg.clone()).variant {
+        ASTVal::Array(vs) => vs,
+        _ => unreachable!()
+      };
+
+      let mut paths = Vec::new();
+      for p in patterns {
+        match p.variant {
+          ASTVal::String(ref pattern) => {
+            let found = glob::glob(&dirs::expand_tilde(pattern));
+            if strict && found.is_empty() {
+              return Err(error(ErrorKind::Value, s, p.location, "No matches for pattern.", noteformat!("Nothing matched:\n\r{}", pattern)));
+            }
+            paths.extend(found.into_iter().map(|f| AST { variant: ASTVal::String(f), location: loc }));
+          },
+          _ => { return Err(error(ErrorKind::Type, s, p.location, "Invalid argument to glob.", noteformat!("glob expects patterns as strings.  It recieved:\n\r{}", rarg))); }
+        }
+      }
+      Ok(AST { variant: ASTVal::Array(paths), location: loc })
+    },
+
+    "files" => {
+      // -r may walk a whole tree
+      if fail_extern {
+        return Err(preview());
+      }
+
+      let (mut all, mut recursive) = (false, false);
+      for o in flatten_args(larg.into_iter().collect()) {
+        match o.variant {
+          ASTVal::Symbol(ref x) if x == "-a" || x == "--all" => { all = true; },
+          ASTVal::Symbol(ref x) if x == "-r" || x == "--recursive" => { recursive = true; },
+          _ => { return Err(error(ErrorKind::Type, s, o.location, "Invalid option to files.", noteformat!("files takes -a to show hidden files and -r to list directories within, on its left.  It recieved:\n\r{}", o))); }
+        }
+      }
+
+      let rarg = inpipe_to_ast(unoptionize(rarg), s, &loc, StdoutCaptureType::Data)?;
+      let dir = match rarg.variant {
+        ASTVal::String(ref st) => dirs::expand_tilde(st),
+        ASTVal::Array(ref vs) if vs.is_empty() => ".".to_string(),
+        _ => { return Err(error(ErrorKind::Type, s, rarg.location, "Expected string to indicate directory.", noteformat!("The given value was:\n\r{}", rarg))); }
+      };
+
+      let entries = files::list(Path::new(&dir), all, recursive).map_err(|e| error(ErrorKind::Io, s, rarg.location, format!("Could not list {} — {}", dir, e), "Give files the name of a directory or file."))?;
+      let cols = ["name", "type", "size", "modified", "permissions", "target"].map(String::from).to_vec();
+      let string = |x: String| AST { variant: ASTVal::String(x), location: loc };
+      Ok(AST { variant: ASTVal::Table(cols, entries.into_iter().map(|e| Vec::from([
+        string(e.name),
+        string(e.kind.to_string()),
+        AST { variant: ASTVal::Num(Number::from(e.size as i64)), location: loc },
+        string(e.modified),
+        string(e.permissions),
+        e.target.map(string).unwrap_or(AST { variant: ASTVal::Null, location: loc })
+      ])).collect()), location: loc })
+    },
+
+    "getenv" => {
+      let rarg = unoptionize(rarg);
+      match rarg.variant {
+        // everything, as name and value pairs
+        ASTVal::Array(ref vs) if vs.is_empty() => {
+          let mut vars = env::vars().collect::<Vec<(String, String)>>();
+          vars.sort();
+          Ok(AST { variant: ASTVal::Array(vars.into_iter().map(|(k, v)| AST { variant: ASTVal::Array(Vec::from([
+            AST { variant: ASTVal::String(k), location: loc },
+            AST { variant: ASTVal::String(v), location: loc }
+          ])), location: loc }).collect()), location: loc })
+        },
+        // an unset variable is empty, as in other shells
+        ASTVal::String(ref k) => Ok(env::var(k).map(|v| AST { variant: ASTVal::String(v), location: loc }).unwrap_or(nilarr())),
+        _ => Err(error(ErrorKind::Type, s, rarg.location, "Invalid argument to getenv.", noteformat!("getenv expects the name of a variable, or nothing.  It recieved:\n\r{}", rarg)))
+      }
+    },
+
+    "export" => {
+      if fail_extern {
+        return Err(preview());
+      }
+
+      let rarg = unoptionize(rarg);
+      // with no value, a name assigned in brie is exported
+      let (name, v) = match larg {
+        Some(name) => (name, rarg),
+        None => match rarg.variant {
+          ASTVal::String(ref k) => match env.get(k) {
+            Some(v) => (rarg.clone(), v.clone()),
+            None => { return Err(error(ErrorKind::Name, s, rarg.location, format!("Unknown identifier {}.", k), "Give export a value on its right and the name on its left, or assign the name first.")); }
+          },
+          _ => (rarg.clone(), rarg)
+        }
+      };
+
+      match (&name.variant, env_value(&v)) {
+        (ASTVal::String(k), Some(val)) if valid_env_name(k) => {
+          env::set_var(k, val);
+          Ok(v)
+        },
+        (ASTVal::String(k), Some(_)) => Err(error(ErrorKind::Value, s, name.location, "Invalid variable name.", noteformat!("A variable name may not be empty or hold = or NUL.  It was:\n\r{}", k))),
+        (ASTVal::String(_), None) => Err(error(ErrorKind::Type, s, v.location, "Invalid value to export.", noteformat!("Variables hold strings or numbers.  The value was:\n\r{}", v))),
+        _ => Err(error(ErrorKind::Type, s, name.location, "Invalid argument to export.", noteformat!("export expects the name of a variable.  It recieved:\n\r{}", name)))
+      }
+    },
+
+    "unset" => {
+      if fail_extern {
+        return Err(preview());
+      }
+
+      let rarg = unoptionize(rarg);
+      match rarg.variant {
+        ASTVal::String(ref k) if valid_env_name(k) => {
+          env::remove_var(k);
+          Ok(nilarr())
+        },
+        _ => Err(error(ErrorKind::Type, s, rarg.location, "Invalid argument to unset.", noteformat!("unset expects the name of a variable.  It recieved:\n\r{}", rarg)))
+      }
+    },
     
     comm => {
       if fail_extern {
-        return Err("[cmd]".to_string());
+        return Err(preview());
       }
 
       let rr = unoptionize(rarg);
@@ -1035,7 +2038,7 @@ fn scalar_function<S: AsRef<str>>(name: S, larg: Option<AST>, rarg: Option<AST>,
             _ => Vec::from([rr])
           },
           Box::new(unoptionize(larg)),
-          redr
+          redr.clone()
         ),
         location: loc 
       })
@@ -1043,7 +2046,7 @@ fn scalar_function<S: AsRef<str>>(name: S, larg: Option<AST>, rarg: Option<AST>,
   }
 }
 
-fn ranked_fncall(fun: AST, larg: Option<AST>, rarg: Option<AST>, s: &String, lrank: i32, rrank: i32, env: &mut HashMap<String, AST>, fail_extern: bool, redr: Redirect) -> Result<AST, String> {
+fn ranked_fncall(fun: AST, larg: Option<AST>, rarg: Option<AST>, s: &String, lrank: i32, rrank: i32, env: &mut Env, fail_extern: bool, redr: &Redirect) -> Result<AST, Error> {
   let loc = fun.location;
   
   let lrank = if lrank < 0 { fathometer(larg.as_ref().unwrap_or(&nilarr())) - lrank - 1 } else { lrank };
@@ -1055,31 +2058,63 @@ fn ranked_fncall(fun: AST, larg: Option<AST>, rarg: Option<AST>, s: &String, lra
       _ => call_function(larg, fun, rarg, s, fail_extern, redr, env)
     }
   } else if lrank == 0 {
-    let naerr = Err(error(s, loc.col, loc.len, "Attempting to apply rankwise to nonarray.", noteformat!("The right rank necessitated an array, but there was instead:\n\r{}", rarg.as_ref().unwrap_or(&nilarr()))));
+    let naerr = Err(error(ErrorKind::Type, s, loc, "Attempting to apply rankwise to nonarray.", noteformat!("The right rank necessitated an array, but there was instead:\n\r{}", rarg.as_ref().unwrap_or(&nilarr()))));
     
-    if let Some(ASTVal::Array(vs)) = rarg.map(|x| x.variant) {
-      let mut res = Vec::new();
-      for v in vs {
-        res.push(ranked_fncall(fun.clone(), larg.clone(), Some(v), s, lrank, rrank - 1, env, fail_extern, redr)?);
-      }
-      Ok(AST { variant: ASTVal::Array(res), location: loc })
-    } else {
-      naerr
+    match rarg.map(|x| x.variant) {
+      Some(ASTVal::Array(vs)) => {
+        let mut res = Vec::new();
+        for v in vs {
+          res.push(ranked_fncall(fun.clone(), larg.clone(), Some(v), s, lrank, rrank - 1, env, fail_extern, redr)?);
+        }
+        Ok(AST { variant: ASTVal::Array(res), location: loc })
+      },
+      // a dict is walked over its values, keeping its keys
+      Some(ASTVal::Dict(es)) => {
+        let mut res = Vec::new();
+        for (k, v) in es {
+          res.push((k, ranked_fncall(fun.clone(), larg.clone(), Some(v), s, lrank, rrank - 1, env, fail_extern, redr)?));
+        }
+        Ok(AST { variant: ASTVal::Dict(res), location: loc })
+      },
+      // a table is walked over its rows, each as a dict
+      Some(ASTVal::Table(cs, rs)) => {
+        let mut res = Vec::new();
+        for v in table_rows(&cs, rs, loc) {
+          res.push(ranked_fncall(fun.clone(), larg.clone(), Some(v), s, lrank, rrank - 1, env, fail_extern, redr)?);
+        }
+        Ok(from_row_results(res, loc))
+      },
+      _ => naerr
     }
   } else if rrank == 0 {
-    let naerr = Err(error(s, loc.col, loc.len, "Attempting to apply rankwise to nonarray.", noteformat!("The left rank necessitated an array, but there was instead:\n\r{}", larg.as_ref().unwrap_or(&nilarr()))));
-    if let Some(ASTVal::Array(vs)) = larg.map(|x| x.variant) {
-      let mut res = Vec::new();
-      for v in vs {
-        res.push(ranked_fncall(fun.clone(), Some(v), rarg.clone(), s, lrank - 1, rrank, env, fail_extern, redr)?);
-      }
-      Ok(AST { variant: ASTVal::Array(res), location: loc })
-    } else {
-      naerr
+    let naerr = Err(error(ErrorKind::Type, s, loc, "Attempting to apply rankwise to nonarray.", noteformat!("The left rank necessitated an array, but there was instead:\n\r{}", larg.as_ref().unwrap_or(&nilarr()))));
+    match larg.map(|x| x.variant) {
+      Some(ASTVal::Array(vs)) => {
+        let mut res = Vec::new();
+        for v in vs {
+          res.push(ranked_fncall(fun.clone(), Some(v), rarg.clone(), s, lrank - 1, rrank, env, fail_extern, redr)?);
+        }
+        Ok(AST { variant: ASTVal::Array(res), location: loc })
+      },
+      Some(ASTVal::Dict(es)) => {
+        let mut res = Vec::new();
+        for (k, v) in es {
+          res.push((k, ranked_fncall(fun.clone(), Some(v), rarg.clone(), s, lrank - 1, rrank, env, fail_extern, redr)?));
+        }
+        Ok(AST { variant: ASTVal::Dict(res), location: loc })
+      },
+      Some(ASTVal::Table(cs, ls)) => {
+        let mut res = Vec::new();
+        for v in table_rows(&cs, ls, loc) {
+          res.push(ranked_fncall(fun.clone(), Some(v), rarg.clone(), s, lrank - 1, rrank, env, fail_extern, redr)?);
+        }
+        Ok(from_row_results(res, loc))
+      },
+      _ => naerr
     }
   } else {
-    let lengtherror = Err(error(s, loc.col, loc.len, "Length mismatch.", noteformat!("While applying rankwise, the left hand side was:\n\r{}\n\rBut the right hand side was:\n\r{}", larg.as_ref().unwrap_or(&nilarr()), rarg.as_ref().unwrap_or(&nilarr()))));
-    let arrerror = Err(error(s, loc.col, loc.len, "Attempting to apply rankwise to nonarray.", noteformat!("The left and right ranks necessitated arrays, but the left hand side was:\n\r{}\n\rAnd the right hand side was:\n\r{}", larg.as_ref().unwrap_or(&nilarr()), rarg.as_ref().unwrap_or(&nilarr()))));
+    let lengtherror = Err(error(ErrorKind::Value, s, loc, "Length mismatch.", noteformat!("While applying rankwise, the left hand side was:\n\r{}\n\rBut the right hand side was:\n\r{}", larg.as_ref().unwrap_or(&nilarr()), rarg.as_ref().unwrap_or(&nilarr()))));
+    let arrerror = Err(error(ErrorKind::Type, s, loc, "Attempting to apply rankwise to nonarray.", noteformat!("The left and right ranks necessitated arrays, but the left hand side was:\n\r{}\n\rAnd the right hand side was:\n\r{}", larg.as_ref().unwrap_or(&nilarr()), rarg.as_ref().unwrap_or(&nilarr()))));
 
     let (lvar, rvar) = (larg.map(|x| x.variant), rarg.map(|x| x.variant));
     
@@ -1099,16 +2134,29 @@ fn ranked_fncall(fun: AST, larg: Option<AST>, rarg: Option<AST>, s: &String, lra
           arrerror
         }
       },
-      None => {
-        if let Some(ASTVal::Array(rs)) = rvar {
+      None => match rvar {
+        Some(ASTVal::Array(rs)) => {
           let mut res = Vec::new();
           for r in rs {
             res.push(ranked_fncall(fun.clone(), None, Some(r), s, lrank - 1, rrank - 1, env, fail_extern, redr)?);
           }
           Ok(AST { variant: ASTVal::Array(res), location: loc })
-        } else {
-          arrerror
-        }
+        },
+        Some(ASTVal::Dict(es)) => {
+          let mut res = Vec::new();
+          for (k, r) in es {
+            res.push((k, ranked_fncall(fun.clone(), None, Some(r), s, lrank - 1, rrank - 1, env, fail_extern, redr)?));
+          }
+          Ok(AST { variant: ASTVal::Dict(res), location: loc })
+        },
+        Some(ASTVal::Table(cs, rs)) => {
+          let mut res = Vec::new();
+          for r in table_rows(&cs, rs, loc) {
+            res.push(ranked_fncall(fun.clone(), None, Some(r), s, lrank - 1, rrank - 1, env, fail_extern, redr)?);
+          }
+          Ok(from_row_results(res, loc))
+        },
+        _ => arrerror
       },
       _ => arrerror
     }
@@ -1122,7 +2170,9 @@ fn is_command<S: AsRef<str>>(f: S) -> bool {
 fn rankof_idfn<S: AsRef<str>>(f: S, isdyad: bool) -> (i32, i32) {
   let f = f.as_ref();
   
-  if is_command(f) || f == "cd" || f == "exit" || f == "pipe" || f == "list" || f == "csv" || f == "json" {
+  if is_command(f) || f == "try" || f == "if" || f == "else" || f == "getenv" || f == "export" || f == "unset" || f == "glob" || f == "pushd" || f == "popd" || f == "dirs" || f == "dict" || f == "keys" || f == "values" || f == "table" || f == "files" {
+    (0, 0)
+  } else if f == "cd" || f == "exit" || f == "pipe" || f == "withenv" || f == "list" || f == "csv" || f == "json" {
     (-1, 0)
   } else if f == CHARSET::ShapeLength || f == CHARSET::Concat || f == CHARSET::Transpose {
     (0, 0)
@@ -1155,7 +2205,7 @@ enum NumericMatrix {
   Vector(Vec<NumericMatrix>)
 }
 
-fn ranked_fixpoint(f: AST, larg: Option<AST>, rarg: AST, times: NumericMatrix, s: &String, fail_extern: bool, redr: Redirect, env: &mut HashMap<String, AST>) -> Result<AST, String> {
+fn ranked_fixpoint(f: AST, larg: Option<AST>, rarg: AST, times: NumericMatrix, s: &String, fail_extern: bool, redr: &Redirect, env: &mut Env) -> Result<AST, Error> {
   match times {
     NumericMatrix::Num(times) => {
       let mut result = rarg;
@@ -1175,7 +2225,7 @@ fn ranked_fixpoint(f: AST, larg: Option<AST>, rarg: AST, times: NumericMatrix, s
   }
 }
 
-fn fixpoint(f: AST, larg: Option<AST>, rarg: AST, times: NumericMatrix, is_fixpoint: bool, s: &String, fail_extern: bool, redr: Redirect, env: &mut HashMap<String, AST>) -> Result<AST, String> {
+fn fixpoint(f: AST, larg: Option<AST>, rarg: AST, times: NumericMatrix, is_fixpoint: bool, s: &String, fail_extern: bool, redr: &Redirect, env: &mut Env) -> Result<AST, Error> {
   if is_fixpoint {
     let mut result = rarg;
     let mut tries = 0;
@@ -1191,7 +2241,7 @@ fn fixpoint(f: AST, larg: Option<AST>, rarg: AST, times: NumericMatrix, is_fixpo
       tries += 1;
       if tries > 32768 {
         let l = result.location;
-        return Err(error(s, l.col, l.len, "Failed to find fixpoint.", "After iterating 2^15 times, no fixpoint was found."));
+        return Err(error(ErrorKind::Value, s, l, "Failed to find fixpoint.", "After iterating 2^15 times, no fixpoint was found."));
       }
     }
 
@@ -1201,10 +2251,10 @@ fn fixpoint(f: AST, larg: Option<AST>, rarg: AST, times: NumericMatrix, is_fixpo
   return ranked_fixpoint(f, larg, rarg, times, s, fail_extern, redr, env)
 }
 
-fn numerify_vector<S: AsRef<str>>(v: AST, s: S) -> Result<NumericMatrix, String> {
+fn numerify_vector<S: AsRef<str>>(v: AST, s: S) -> Result<NumericMatrix, Error> {
   let s = s.as_ref();
   match v.variant {
-    ASTVal::Num(i) if i > 0.0 && i.fract() == 0.0 => Ok(NumericMatrix::Num(i as u64 as usize)),
+    ASTVal::Num(i) if i.as_i64().is_some_and(|i| i > 0) => Ok(NumericMatrix::Num(i.as_i64().unwrap() as usize)),
     ASTVal::Array(vs) => {
       let mut res = Vec::new();
       for v in vs {
@@ -1212,11 +2262,11 @@ fn numerify_vector<S: AsRef<str>>(v: AST, s: S) -> Result<NumericMatrix, String>
       }
       Ok(NumericMatrix::Vector(res))
     }
-    _ => Err(error(s, v.location.col, v.location.len, "Invalid argument to iterate.", "Iterate expects an integral argument, a [possibly nested] array of such arguments, or a function which returns such an argument."))?
+    _ => Err(error(ErrorKind::Type, s, v.location, "Invalid argument to iterate.", "Iterate expects an integral argument, a [possibly nested] array of such arguments, or a function which returns such an argument."))?
   }
 }
 
-fn call_function(larg: Option<AST>, fun: AST, rarg: Option<AST>, s: &String, fail_extern: bool, redr: Redirect, env: &mut HashMap<String, AST>) -> Result<AST, String> {
+fn call_function(larg: Option<AST>, fun: AST, rarg: Option<AST>, s: &String, fail_extern: bool, redr: &Redirect, env: &mut Env) -> Result<AST, Error> {
   let (larg, rarg) = match respect_fill(&fun, env) {
     false => match fill_from_right(&fun, env) {
       true => if rarg.is_none() { (None, larg) } else { (larg, rarg) },
@@ -1226,9 +2276,42 @@ fn call_function(larg: Option<AST>, fun: AST, rarg: Option<AST>, s: &String, fai
   };
   
   match fun.variant {
+    ASTVal::Lambda(ref lname, ref rname, ref body, ref closure) => {
+      let loc = fun.location;
+      let larg = larg.map(|a| inpipe_to_ast(a, s, &loc, StdoutCaptureType::Data)).transpose()?;
+      let rarg = inpipe_to_ast(unoptionize(rarg), s, &loc, StdoutCaptureType::Data)?;
+
+      // the arguments, and assignments within the body, live in a scope of their own inside the ones the function was written in.
+      // the caller's names are not seen
+      enter_call(env, s, loc)?;
+      let mut local = closure.as_ref().map_or_else(|| env.child(), |c| c.call(env));
+      if let Some(lname) = lname {
+        local.insert(lname.to_string(), larg.unwrap_or(nilarr()));
+      }
+      local.insert(rname.to_string(), rarg);
+
+      let res = resolve(*body.clone(), &local, s)
+        .and_then(|body| eval_command(body, s, &mut local, fail_extern))
+        .and_then(|v| inpipe_to_ast(v, s, &loc, StdoutCaptureType::Data));
+      leave_call();
+      res
+    },
+
+    // a name left inside an alias is looked up only when called, so an alias may refer to itself
+    ASTVal::Ident(ref x) if !token::in_operators(x) && env.get(x).is_some() => {
+      let f = resolve(fun.clone(), env, s)?;
+      enter_call(env, s, fun.location)?;
+      let res = call_function(larg, f, rarg, s, fail_extern, redr, env);
+      leave_call();
+      res
+    },
+
     ASTVal::Ident(ref x) => {
-      let lrank = rankof_idfn(&x, larg.is_some() && rarg.is_some()).0;
-      let rrank = rankof_idfn(&x, larg.is_some() && rarg.is_some()).1;
+      let (lrank, rrank) = match rarg.as_ref().map(|r| &r.variant) {
+        // a table takes its column names or row numbers all at once
+        Some(ASTVal::Table(..)) if x.as_str() == CHARSET::Index => (0, 0),
+        _ => rankof_idfn(&x, larg.is_some() && rarg.is_some())
+      };
       
       ranked_fncall(fun, larg, rarg, s, lrank, rrank, env, fail_extern, redr)
     },
@@ -1289,13 +2372,18 @@ fn call_function(larg: Option<AST>, fun: AST, rarg: Option<AST>, s: &String, fai
         },
         x if x == CHARSET::Where => {
           let mut res = Vec::new();
-          let (_larg, rarg) = (inpipe_to_ast(unoptionize(larg), s, &f.location, StdoutCaptureType::Data)?, arrayifyast(inpipe_to_ast(unoptionize(rarg), s, &f.location, StdoutCaptureType::Data)?));
+          let (_larg, rarg) = (inpipe_to_ast(unoptionize(larg), s, &f.location, StdoutCaptureType::Data)?, inpipe_to_ast(unoptionize(rarg), s, &f.location, StdoutCaptureType::Data)?);
+          // each row of a table is given as a dict
+          let rarg = match rarg.variant {
+            ASTVal::Table(cs, rs) => AST { variant: ASTVal::Array(table_rows(&cs, rs, rarg.location)), location: rarg.location },
+            v => arrayifyast(AST { variant: v, location: rarg.location })
+          };
 
           if let ASTVal::Array(vs) = rarg.variant {
             let mut i = 0;
             for v in vs {
               if is_truthy(&call_function(None, (*f).clone(), Some(v), s, fail_extern, redr, env)?) {
-                res.push(AST { variant: ASTVal::Num(i as f32), location: f.location })
+                res.push(AST { variant: ASTVal::Num(Number::from(i as i64)), location: f.location })
               }
               i += 1;
             }
@@ -1304,15 +2392,15 @@ fn call_function(larg: Option<AST>, fun: AST, rarg: Option<AST>, s: &String, fai
           Ok(AST { variant: ASTVal::Array(res), location: f.location })
         },
         x if x == CHARSET::Each => {
-          let canned_err = error(s, fun.location.col, fun.location.len, "Rank expects integral numeric right argument.", noteformat!("The value given was:\n\r{}", v));
+          let canned_err = error(ErrorKind::Type, s, fun.location, "Rank expects integral numeric right argument.", noteformat!("The value given was:\n\r{}", v));
 
           match v.variant {
-            ASTVal::Num(i) if i.fract() == 0.0 => ranked_fncall(*f, larg, rarg, s, i as i32, i as i32, 
+            ASTVal::Num(i) if i.as_i64().is_some() => ranked_fncall(*f, larg, rarg, s, i.as_i64().unwrap() as i32, i.as_i64().unwrap() as i32, 
 env, fail_extern, redr),
             ASTVal::Array(vs) if vs.len() == 2 => {
               match vs[0].variant {
-                ASTVal::Num(il) if il.fract() == 0.0 => match vs[1].variant {
-                  ASTVal::Num(ir) if ir.fract() == 0.0 => ranked_fncall(*f, larg, rarg, s, il as i32, ir as i32, env, fail_extern, redr),
+                ASTVal::Num(ref il) if il.as_i64().is_some() => match vs[1].variant {
+                  ASTVal::Num(ref ir) if ir.as_i64().is_some() => ranked_fncall(*f, larg, rarg, s, il.as_i64().unwrap() as i32, ir.as_i64().unwrap() as i32, env, fail_extern, redr),
                   _ => Err(canned_err)
                 },
                 _ => Err(canned_err)
@@ -1370,7 +2458,7 @@ env, fail_extern, redr),
           Ok(AST { variant: ASTVal::Array(results), location: l })
         },
           
-        x => Err(error(s, fun.location.col, fun.location.len, format!("Unknown operator {}.", x).as_str(), "This is an internal error."))
+        x => Err(error(ErrorKind::Internal, s, fun.location, format!("Unknown operator {}.", x).as_str(), "This is an internal error."))
       }
     },
 
@@ -1381,6 +2469,9 @@ env, fail_extern, redr),
       call_function(Some(lhs), *f, Some(rhs), s, fail_extern, redr, env)
     },
     
+    // a function before try handles its failure, rather than being applied to what it gives
+    ASTVal::Apply(None, h, Some(t)) if is_try(&fun) => call_function(Some(*h), *t, rarg, s, fail_extern, redr, env),
+
     ASTVal::Apply(ls, f, rs) if ls.is_none() && match &rs { Some(s) if is_fn(&s, env) => true, _ => false } => {
       let intermed = call_function(larg, *rs.unwrap(), rarg, s, fail_extern, redr, env)?;
       call_function(None, *f, Some(intermed), s, fail_extern, redr, env)
@@ -1390,13 +2481,13 @@ env, fail_extern, redr),
       call_function(ls.map(|x| *x).or(larg), *f, rs.map(|x| *x).or(rarg), s, fail_extern, redr, env)
     },
 
-    _ => Err(error(s, fun.location.col, fun.location.len, "Attempting to call non-callable.", noteformat!("The value attempting to be called was:\n\r{}", fun)))
+    _ => Err(error(ErrorKind::Type, s, fun.location, "Attempting to call non-callable.", noteformat!("The value attempting to be called was:\n\r{}", fun)))
   }
 }
 
-fn eval_command(command: AST, s: &String, env: &mut HashMap<String, AST>, fail_extern: bool) -> Result<AST, String> {
+fn eval_command(command: AST, s: &String, env: &mut Env, fail_extern: bool) -> Result<AST, Error> {
   match command.variant {
-    ASTVal::Num(_) | ASTVal::String(_) | ASTVal::Symbol(_) | ASTVal::Command(..) | ASTVal::Ident(_) => Ok(command),
+    ASTVal::Num(_) | ASTVal::Bool(_) | ASTVal::Null | ASTVal::String(_) | ASTVal::Symbol(_) | ASTVal::Command(..) | ASTVal::Ident(_) | ASTVal::Job(_) | ASTVal::Lambda(..) | ASTVal::Table(..) => Ok(command),
     ASTVal::Array(vs) => {
       let mut newvec = Vec::new();
       for v in vs {
@@ -1409,6 +2500,10 @@ fn eval_command(command: AST, s: &String, env: &mut HashMap<String, AST>, fail_e
       
       Ok(AST { variant: ASTVal::Array(newvec), location: command.location })
     }
+    // the values of a literal, as with an array's
+    ASTVal::Dict(es) => Ok(AST { variant: ASTVal::Dict(
+      es.into_iter().map(|(k, v)| Ok((k, eval_command(v, s, env, fail_extern)?))).collect::<Result<Vec<(String, AST)>, Error>>()?
+    ), location: command.location }),
     ASTVal::Operator(f, o, v) => {
       let val = Box::new(
         if is_fn(&v, env) {
@@ -1419,6 +2514,8 @@ fn eval_command(command: AST, s: &String, env: &mut HashMap<String, AST>, fail_e
       );
       Ok(AST { variant: ASTVal::Operator(f, o, val), location: command.location })
     },
+    // a function literal on its own is a value, not a call
+    ASTVal::Apply(None, f, None) if matches!(f.variant, ASTVal::Lambda(..)) => Ok(*f),
     ASTVal::Apply(a, f, b) => {
       let is_lazy = is_lazy(&f);
       
@@ -1454,29 +2551,68 @@ fn eval_command(command: AST, s: &String, env: &mut HashMap<String, AST>, fail_e
         }
       );
       
-      call_function(l, f, r, s, fail_extern, Redirect { 
+      call_function(l, f, r, s, fail_extern, &Redirect { 
         stdout: ToRedirect::ToStdout,
-        stderr: ToRedirect::ToStderr
+        stderr: ToRedirect::ToStderr,
+        stdin: None,
+        env: Vec::new()
       }, env)
     },
 
-    ASTVal::Assign(n, v) => {
+    ASTVal::Assign(n, v, outer) => {
       let val = eval_command(*v, s, env, fail_extern)?;
 
-      if !fail_extern { env.insert(n, val.clone()); }
+      if !fail_extern { bind(env, n, val.clone(), outer); }
       Ok(val)
     },
-    ASTVal::AliasAssign(n, v) => {
-      if !fail_extern { env.insert(n, *v.clone()); }
+    ASTVal::AliasAssign(n, v, outer) => {
+      if !fail_extern { bind(env, n, *v.clone(), outer); }
       Ok(*v)
-    }
+    },
+
+    // each statement is resolved only once those before it have run, so it sees what they bound
+    ASTVal::Block(vs) => {
+      let mut last = nilarr();
+      let count = vs.len();
+      for (i, st) in vs.into_iter().enumerate() {
+        let st = resolve(st, env, s)?;
+        // a function's own names are dropped when it returns, so they may be bound even in the preview
+        let r = match st.variant {
+          ASTVal::Assign(n, v, false) => {
+            let val = eval_command(*v, s, env, fail_extern)?;
+            env.insert(n, val.clone());
+            val
+          },
+          ASTVal::AliasAssign(n, v, false) => {
+            env.insert(n, *v.clone());
+            *v
+          },
+          _ => eval_command(st, s, env, fail_extern)?
+        };
+        let l = r.location;
+        last = if i + 1 < count { inpipe_to_ast(r, s, &l, StdoutCaptureType::None)? } else { r };
+      }
+      Ok(last)
+    },
 
     ASTVal::SymbolList(..) => unreachable!()
   }
 }
 
-pub fn eval_commands(commands: Vec<AST>, s: &String, env: &mut HashMap<String, AST>, fail_extern: bool) -> Result<AST, String> {
-  let mut v = AST { variant: ASTVal::Array(Vec::new()), location: Location { col: 0, len: 0 } };
+fn bind(env: &mut Env, name: String, v: AST, outer: bool) {
+  if outer {
+    env.insert_outer(name, v);
+  } else {
+    env.insert(name, v);
+  }
+}
+
+pub fn eval_commands(commands: Vec<AST>, s: &String, env: &mut Env, fail_extern: bool) -> Result<AST, Error> {
+  let mut v = AST { variant: ASTVal::Array(Vec::new()), location: Location { col: 0, len: 0, line: 0 } };
+  // a line which runs no process succeeds
+  if !fail_extern {
+    reset_status();
+  }
   
   for command in commands {
     let res = eval_command(command, s, env, fail_extern);
@@ -1495,21 +2631,49 @@ pub fn eval_commands(commands: Vec<AST>, s: &String, env: &mut HashMap<String, A
   Ok(v)
 }
 
-pub fn resolve(node: AST, env: &HashMap<String, AST>, s: &String) -> Result<AST, String> {
+pub fn resolve(node: AST, env: &Env, s: &String) -> Result<AST, Error> {
   match node.variant {
     ASTVal::Ident(ref i) => {
-      if token::in_operators(&i) || which(i).is_ok() { Ok(node) } else {
+      // names shadow commands, as they do when parsing
+      if token::in_operators(&i) { Ok(node) } else {
         match env.get(i) {
-          None => Err(error(s, node.location.col, node.location.len, format!("Unknown identifier {}.", i), "This is a typo.")),
+          // read-only, and read as the line is resolved, before it runs anything itself
+          None if i == "STATUS" => Ok(AST { variant: ASTVal::Num(Number::from(last_status() as i64)), location: node.location }),
+          None if i == "PIPESTATUS" => Ok(AST { variant: ASTVal::Array(pipe_status().into_iter().map(|c| AST { variant: ASTVal::Num(Number::from(c as i64)), location: node.location }).collect()), location: node.location }),
+          None if which(i).is_ok() => Ok(node),
+          None => Err(error(ErrorKind::Name, s, node.location, format!("Unknown identifier {}.", i), "This is a typo.")),
           Some(v) => Ok(v.clone())
         }
       }
     },
     
-    ASTVal::Assign(name, v) => Ok(AST { variant: ASTVal::Assign(name, Box::new(resolve(*v, env, s)?)), location: node.location }),
-    ASTVal::AliasAssign(name, v) => Ok(AST { variant: ASTVal::AliasAssign(name, Box::new(resolve(*v, env, s)?)), location: node.location }),
+    ASTVal::Assign(name, v, outer) => Ok(AST { variant: ASTVal::Assign(name, Box::new(resolve(*v, env, s)?), outer), location: node.location }),
+    // an alias's own name is left for when it is called, so that it may refer to itself
+    ASTVal::AliasAssign(name, v, outer) => {
+      let mut inner = env.child();
+      inner.insert(name.clone(), AST { variant: ASTVal::Ident(name.clone()), location: node.location });
+      let v = resolve(*v, &inner, s);
+      // functions within keep the scope, and must find the alias itself there once it is bound
+      inner.remove(&name);
+      Ok(AST { variant: ASTVal::AliasAssign(name, Box::new(v?), outer), location: node.location })
+    },
+
+    ASTVal::Num(_) | ASTVal::Bool(_) | ASTVal::Null | ASTVal::Symbol(_) | ASTVal::String(_) | ASTVal::SymbolList(_) | ASTVal::Job(_) | ASTVal::Table(..) => Ok(node),
+
+    ASTVal::Dict(es) => Ok(AST { variant: ASTVal::Dict(
+      es.into_iter().map(|(k, v)| Ok((k, resolve(v, env, s)?))).collect::<Result<Vec<(String, AST)>, Error>>()?
+    ), location: node.location }),
 
-    ASTVal::Num(_) | ASTVal::Symbol(_) | ASTVal::String(_) | ASTVal::SymbolList(_) => Ok(node),
+    // a function keeps the scopes it is written in, and its body is resolved against them when called
+    ASTVal::Lambda(l, r, body, None) => Ok(AST { variant: ASTVal::Lambda(l, r, body, Some(env.capture())), location: node.location }),
+    ASTVal::Lambda(..) | ASTVal::Block(..) => Ok(node),
+
+    // the arguments to `try` may fail to resolve; that failure is for `try` to catch
+    ASTVal::Apply(a, b, c) if matches!(b.variant, ASTVal::Ident(ref i) if i == "try") => Ok(AST { variant: ASTVal::Apply(
+      a.map(|a| resolve(*a.clone(), env, s).unwrap_or(*a)).map(Box::new),
+      b,
+      c.map(|c| resolve(*c.clone(), env, s).unwrap_or(*c)).map(Box::new)
+    ), location: node.location }),
 
     ASTVal::Apply(a, b, c) => Ok(AST { variant: ASTVal::Apply(
       a.map(|a| resolve(*a, env, s)).map_or(Ok(None), |v| v.map(Some))?.map(Box::new),
@@ -1518,16 +2682,209 @@ pub fn resolve(node: AST, env: &HashMap<String, AST>, s: &String) -> Result<AST,
     ), location: node.location }),
 
     ASTVal::Array(vs) => Ok(AST { variant: ASTVal::Array(
-      vs.into_iter().map(|v| resolve(v, env, s)).collect::<Result<Vec<AST>, String>>()?
+      vs.into_iter().map(|v| resolve(v, env, s)).collect::<Result<Vec<AST>, Error>>()?
     ), location: node.location }),
 
     ASTVal::Operator(f, o, v) => Ok(AST { variant: ASTVal::Operator(Box::new(resolve(*f, env, s)?), o, Box::new(resolve(*v, env, s)?)), location: node.location }),
 
     ASTVal::Command(c, ags, stdin, redr) => Ok(AST { variant: ASTVal::Command(
       c,
-      ags.into_iter().map(|v| resolve(v, env, s)).collect::<Result<Vec<AST>, String>>()?,
+      ags.into_iter().map(|v| resolve(v, env, s)).collect::<Result<Vec<AST>, Error>>()?,
       Box::new(resolve(*stdin, env, s)?),
       redr
     ), location: node.location })
   }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{parse, token};
+
+  // evaluates each line in turn in one environment, as the repl would, giving the last value
+  fn run(lines: &[&str]) -> Result<AST, Error> {
+    let mut env = Env::new();
+    let mut last = nilarr();
+    for line in lines {
+      let s = line.to_string();
+      let mut toks = token::tokenize(&s)?;
+      let cmd = parse::parse_commands(&mut toks, &env, &s)?;
+      let cmd = cmd.map(|c| resolve(c, &env, &s)).transpose()?;
+      last = eval_commands(cmd.into_iter().collect(), &s, &mut env, false)?;
+    }
+    Ok(last)
+  }
+
+  fn num(v: AST) -> i64 {
+    match v.variant {
+      ASTVal::Num(n) => n.as_i64().expect("a whole number"),
+      _ => panic!("expected a number, got {}", v)
+    }
+  }
+
+  #[test]
+  fn try_handler_nested_on_the_right() {
+    assert_eq!(num(run(&["f; fn e (40)", "1 + (f try (num \"abc\"))"]).unwrap()), 41);
+    assert_eq!(num(run(&["1 + (0 try (num \"abc\"))"]).unwrap()), 1);
+  }
+
+  #[test]
+  fn try_handler_nested_on_the_left() {
+    assert_eq!(num(run(&["f; fn e (40)", "(f try (num \"abc\")) + 2"]).unwrap()), 42);
+    assert_eq!(num(run(&["(0 try (num \"abc\")) + 1"]).unwrap()), 1);
+  }
+
+  #[test]
+  fn try_handler_only_on_failure() {
+    assert_eq!(num(run(&["f; fn e (40)", "1 + (f try (num \"5\"))"]).unwrap()), 6);
+    assert_eq!(num(run(&["f; fn e (40)", "f try (num \"5\")"]).unwrap()), 5);
+  }
+
+  #[test]
+  fn try_handler_nested_in_a_process_argument() {
+    let v = run(&["f; fn e (\"fallback\")", "collect (echo (f try (num \"abc\")))"]).unwrap();
+    match v.variant {
+      ASTVal::Array(vs) => assert!(matches!(&vs[0].variant, ASTVal::String(out) if out == "fallback\n")),
+      _ => panic!("expected what collect gives, got {}", v)
+    }
+  }
+
+  #[test]
+  fn try_without_handler_nested() {
+    let v = run(&["\"a\" , (try (num \"abc\"))"]).unwrap();
+    match v.variant {
+      ASTVal::Array(vs) => assert_eq!(vs.len(), 5),
+      _ => panic!("expected an array, got {}", v)
+    }
+  }
+
+  #[test]
+  fn json_numbers_keep_their_form() {
+    let v = run(&[r#"json "[123456789012345678901234567890, -5, 2e3, 1.0, 1e400]""#]).unwrap();
+    let ns = match v.variant {
+      ASTVal::Array(vs) => vs.into_iter().map(|v| match v.variant { ASTVal::Num(n) => n, _ => panic!("expected a number") }).collect::<Vec<Number>>(),
+      _ => panic!("expected an array")
+    };
+    assert_eq!(ns[0].to_string(), "123456789012345678901234567890");
+    assert!(matches!(ns[1], Number::Int(-5)));
+    assert!(matches!(ns[2], Number::Float(f) if f == 2000.0));
+    assert!(matches!(ns[3], Number::Float(f) if f == 1.0));
+    assert!(matches!(ns[4], Number::Float(f) if f.is_infinite()));
+  }
+
+  #[test]
+  fn json_numbers_round_trip() {
+    let v = run(&[r#"json (json "[12345678901234567890123, -3, 100.0, 1e3, 0.5]")"#]).unwrap();
+    assert!(matches!(v.variant, ASTVal::String(s) if s == "[12345678901234567890123,-3,100.0,1000.0,0.5]"));
+    // a repeated key leaves the numbers after it exact
+    let v = run(&[r#"json (json "{\"a\": 1, \"a\": 2, \"b\": 123456789012345678901234567890}")"#]).unwrap();
+    assert!(matches!(v.variant, ASTVal::String(s) if s == r#"{"a":2,"b":123456789012345678901234567890}"#));
+    assert!(run(&[r#"json "[1,]""#]).is_err());
+    assert!(run(&[r#"json "01""#]).is_err());
+  }
+
+  #[test]
+  fn json_strings_hold_no_number_literals() {
+    let v = run(&[r#"json "[\"12\\\"3\", 98765432109876543210]""#]).unwrap();
+    match v.variant {
+      ASTVal::Array(vs) => assert!(matches!(&vs[1].variant, ASTVal::Num(n) if n.to_string() == "98765432109876543210")),
+      _ => panic!("expected an array")
+    }
+  }
+
+  #[test]
+  fn dict_literals() {
+    let v = run(&["n; 2", r#"{name: "brie", "a key": (n + 1), name: "cheese", inner: {:}}"#]).unwrap();
+    match v.variant {
+      ASTVal::Dict(es) => {
+        assert_eq!(es.iter().map(|(k, _)| k.as_str()).collect::<Vec<&str>>(), ["name", "a key", "inner"]);
+        assert!(matches!(&es[0].1.variant, ASTVal::String(x) if x == "cheese"));
+        assert_eq!(num(es[1].1.clone()), 3);
+        assert!(matches!(&es[2].1.variant, ASTVal::Dict(e) if e.is_empty()));
+      },
+      _ => panic!("expected a dict, got {}", v)
+    }
+    assert!(run(&["{a: 1 2}"]).is_err());
+    assert!(run(&["{a:}"]).is_err());
+    assert!(run(&["{a: 1,}"]).is_err());
+  }
+
+  #[test]
+  fn dicts_print_as_they_are_written() {
+    let written = r#"{"a b": "x\ty", n: (1 (2 3)), m: {:}, k: {q: -1.5}}"#;
+    let v = run(&[written]).unwrap();
+    assert_eq!(v.to_tree(), written);
+    assert!(equality(&run(&[&v.to_tree()]).unwrap(), &v));
+  }
+
+  #[test]
+  fn kinds_sort_in_a_fixed_order() {
+    let order = run(&[r#"< json "[{\"a\": 1}, [1], \"b\", 2, true, null]""#]).unwrap();
+    match order.variant {
+      ASTVal::Array(is) => assert_eq!(is.into_iter().map(num).collect::<Vec<i64>>(), [5, 4, 3, 2, 1, 0]),
+      _ => panic!("expected an array, got {}", order)
+    }
+    assert_eq!(num(run(&[r#"1 < "a""#]).unwrap()), 1);
+    assert_eq!(num(run(&[r#""a" < 1"#]).unwrap()), 0);
+  }
+
+  #[test]
+  fn table_fields_written_as_numbers_are_numbers() {
+    let t = run(&[r#"csv "name,size,zip,ratio\nsrc,4096,01234,1.50\nb.txt,30,90210,-2.5""#]).unwrap();
+    match t.variant {
+      ASTVal::Table(_, rs) => {
+        assert_eq!(num(rs[0][1].clone()), 4096);
+        assert!(matches!(&rs[0][2].variant, ASTVal::String(z) if z == "01234"));
+        assert!(matches!(&rs[0][3].variant, ASTVal::String(r) if r == "1.50"));
+        assert!(matches!(&rs[1][3].variant, ASTVal::Num(Number::Float(f)) if *f == -2.5));
+      },
+      _ => panic!("expected a table, got {}", t)
+    }
+    let sorted = run(&[r#"t; csv "name,size\nsrc,4096\nb.txt,30""#, r#"csv ((< ("size" [ t)) [ t)"#]).unwrap();
+    assert!(matches!(sorted.variant, ASTVal::String(s) if s == "name,size\nb.txt,30\nsrc,4096\n"));
+  }
+
+  // a command takes its whole left argument as stdin, rather than running once for each of its atoms
+  #[test]
+  fn an_array_piped_to_a_command_is_one_stdin() {
+    let v = run(&[r#"("b" "a") | sort | collect"#]).unwrap();
+    match v.variant {
+      ASTVal::Array(vs) => assert!(matches!(&vs[0].variant, ASTVal::String(out) if out == "a\nb\n")),
+      _ => panic!("expected the output of one process, got {}", v)
+    }
+  }
+
+  // a trailing backslash is a scan, so only a pipe carries a line on to the next
+  #[test]
+  fn a_trailing_scan_ends_the_line() {
+    assert!(!token::needs_continuation("s;; + \\"));
+    let v = run(&["s;; + \\", "s 1 2 3"]).unwrap();
+    match v.variant {
+      ASTVal::Array(vs) => assert_eq!(vs.into_iter().map(num).collect::<Vec<i64>>(), vec![1, 3, 6]),
+      _ => panic!("expected a scan, got {}", v)
+    }
+  }
+
+  #[test]
+  fn a_trailing_pipe_continues_the_line() {
+    assert!(token::needs_continuation(r#"("b" "a") |"#));
+    assert!(!token::needs_continuation(r#"("b" "a") || sort"#));
+    let v = run(&["(\"b\" \"a\") |\n  sort | collect"]).unwrap();
+    match v.variant {
+      ASTVal::Array(vs) => assert!(matches!(&vs[0].variant, ASTVal::String(out) if out == "a\nb\n")),
+      _ => panic!("expected the output of one process, got {}", v)
+    }
+  }
+
+  // else alone separates the branches, so an array of two is a branch like any other
+  #[test]
+  fn if_takes_a_two_element_branch_whole() {
+    let v = run(&["1 if (1 2)"]).unwrap();
+    match v.variant {
+      ASTVal::Array(vs) => assert_eq!(vs.into_iter().map(num).collect::<Vec<i64>>(), vec![1, 2]),
+      _ => panic!("expected the whole branch, got {}", v)
+    }
+    assert!(matches!(run(&["0 if (1 2)"]).unwrap().variant, ASTVal::Array(vs) if vs.is_empty()));
+    assert_eq!(num(run(&["0 if ((1 2) else 3)"]).unwrap()), 3);
+    assert_eq!(num(run(&["n; 5", "(n > 9) if (1 else ((n > 3) if (2 else 3)))"]).unwrap()), 2);
+  }
+}
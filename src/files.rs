@@ -0,0 +1,104 @@
+use std::fs;
+use std::io;
+use std::os::unix::fs::{FileTypeExt, MetadataExt, PermissionsExt};
+use std::path::Path;
+
+// one line of a listing; a symlink is described itself rather than what it points to
+pub struct Entry {
+  pub name: String,
+  pub kind: &'static str,
+  pub size: u64,
+  pub modified: String,
+  pub permissions: String,
+  pub target: Option<String>
+}
+
+fn kind(ft: fs::FileType) -> &'static str {
+  match () {
+    _ if ft.is_symlink() => "symlink",
+    _ if ft.is_dir() => "dir",
+    _ if ft.is_file() => "file",
+    _ if ft.is_fifo() => "fifo",
+    _ if ft.is_socket() => "socket",
+    _ if ft.is_block_device() => "block",
+    _ if ft.is_char_device() => "char",
+    _ => "other"
+  }
+}
+
+// rwxr-xr-x, with the setuid, setgid and sticky bits where ls puts them
+fn permissions(mode: u32) -> String {
+  let mut out = String::new();
+  for (shift, special, mark) in [(6, 0o4000, 's'), (3, 0o2000, 's'), (0, 0o1000, 't')] {
+    let bits = mode >> shift;
+    out.push(if bits & 4 != 0 { 'r' } else { '-' });
+    out.push(if bits & 2 != 0 { 'w' } else { '-' });
+    out.push(match (bits & 1 != 0, mode & special != 0) {
+      (true, true) => mark,
+      (false, true) => mark.to_ascii_uppercase(),
+      (true, false) => 'x',
+      (false, false) => '-'
+    });
+  }
+  out
+}
+
+// local time to the minute, which sorts as text
+fn timestamp(secs: i64) -> String {
+  let mut tm: libc::tm = unsafe { std::mem::zeroed() };
+  let t = secs as libc::time_t;
+  if unsafe { libc::localtime_r(&t, &mut tm) }.is_null() {
+    return String::new();
+  }
+  format!("{:04}-{:02}-{:02} {:02}:{:02}", tm.tm_year + 1900, tm.tm_mon + 1, tm.tm_mday, tm.tm_hour, tm.tm_min)
+}
+
+fn entry(path: &Path, name: String) -> io::Result<Entry> {
+  let md = fs::symlink_metadata(path)?;
+  let ft = md.file_type();
+  Ok(Entry {
+    name,
+    kind: kind(ft),
+    size: md.len(),
+    modified: timestamp(md.mtime()),
+    permissions: permissions(md.permissions().mode()),
+    target: if ft.is_symlink() { fs::read_link(path).ok().map(|t| t.to_string_lossy().into_owned()) } else { None }
+  })
+}
+
+// a directory's entries in sorted order, each directory's contents after it when recursing.
+// symlinks to directories are not followed, and a directory which cannot be read partway down is passed over
+fn walk(dir: &Path, prefix: &str, all: bool, recursive: bool, out: &mut Vec<Entry>) -> io::Result<()> {
+  let mut names = fs::read_dir(dir)?
+    .filter_map(|e| e.ok())
+    .map(|e| e.file_name().to_string_lossy().into_owned())
+    .filter(|n| all || !n.starts_with('.'))
+    .collect::<Vec<String>>();
+  names.sort();
+
+  for n in names {
+    let path = dir.join(&n);
+    let Ok(e) = entry(&path, format!("{}{}", prefix, n)) else {
+      continue;
+    };
+    let descend = recursive && e.kind == "dir";
+    let name = e.name.clone();
+    out.push(e);
+    if descend {
+      let _ = walk(&path, &(name + "/"), all, recursive, out);
+    }
+  }
+  Ok(())
+}
+
+// the entries of a directory, or a file by itself
+pub fn list(path: &Path, all: bool, recursive: bool) -> io::Result<Vec<Entry>> {
+  let mut out = Vec::new();
+  // a symlink given by name is followed, as with ls
+  if fs::metadata(path).is_ok_and(|m| m.is_dir()) {
+    walk(path, "", all, recursive, &mut out)?;
+  } else {
+    out.push(entry(path, path.to_string_lossy().into_owned())?);
+  }
+  Ok(out)
+}
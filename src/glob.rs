@@ -0,0 +1,232 @@
+use std::path::{Path, PathBuf};
+
+// {a,b} expands before anything touches the filesystem, innermost braces last
+fn expand_braces(pattern: &str) -> Vec<String> {
+  let chars = pattern.chars().collect::<Vec<char>>();
+
+  let mut depth = 0;
+  let mut open = None;
+  let mut commas = Vec::new();
+  let mut i = 0;
+  while i < chars.len() {
+    match chars[i] {
+      '\\' => { i += 1; },
+      '{' => {
+        if depth == 0 { open = Some(i); commas.clear(); }
+        depth += 1;
+      },
+      ',' if depth == 1 => commas.push(i),
+      '}' if depth > 0 => {
+        depth -= 1;
+        if depth == 0 {
+          let start = open.unwrap();
+          // without a comma the braces are literal, as in other shells
+          if !commas.is_empty() {
+            let before = chars[..start].iter().collect::<String>();
+            let after = chars[i + 1..].iter().collect::<String>();
+            let mut bounds = Vec::from([start]);
+            bounds.extend(commas.iter());
+            bounds.push(i);
+            return bounds.windows(2)
+              .map(|w| chars[w[0] + 1..w[1]].iter().collect::<String>())
+              .flat_map(|alt| expand_braces(&format!("{}{}{}", before, alt, after)))
+              .collect();
+          }
+        }
+      },
+      _ => {}
+    }
+    i += 1;
+  }
+
+  Vec::from([pattern.to_string()])
+}
+
+fn has_magic(component: &str) -> bool {
+  component.contains(['*', '?', '['])
+}
+
+// a bracket expression starting at p[0] == '['; gives whether c matched and the length of the expression
+fn match_class(p: &[char], c: char) -> Option<(bool, usize)> {
+  let mut i = 1;
+  let negate = i < p.len() && (p[i] == '!' || p[i] == '^');
+  if negate { i += 1; }
+
+  let mut matched = false;
+  let mut first = true;
+  while i < p.len() && (first || p[i] != ']') {
+    first = false;
+    let lo = p[i];
+    if i + 2 < p.len() && p[i + 1] == '-' && p[i + 2] != ']' {
+      matched |= lo <= c && c <= p[i + 2];
+      i += 3;
+    } else {
+      matched |= lo == c;
+      i += 1;
+    }
+  }
+
+  // an unclosed bracket is literal
+  if i >= p.len() { None } else { Some((matched != negate, i + 1)) }
+}
+
+// every other piece of a pattern takes exactly one character, so on a mismatch only the last `*` need take one more;
+// earlier ones could not do better.  this keeps a match linear in the pattern for each character of the name
+fn match_here(p: &[char], n: &[char]) -> bool {
+  let (mut pi, mut ni) = (0, 0);
+  let mut star: Option<(usize, usize)> = None; // the pattern after the last `*`, and how much of the name it has taken
+  while ni < n.len() {
+    // the length of the piece of pattern which takes n[ni], if it does
+    let step = match p.get(pi) {
+      Some('*') => {
+        star = Some((pi + 1, ni));
+        pi += 1;
+        continue;
+      },
+      Some('?') => Some(1),
+      Some('[') => match match_class(&p[pi..], n[ni]) {
+        Some((ok, len)) => ok.then_some(len),
+        None => (n[ni] == '[').then_some(1)
+      },
+      Some('\\') if pi + 1 < p.len() => (n[ni] == p[pi + 1]).then_some(2),
+      Some(c) => (n[ni] == *c).then_some(1),
+      None => None
+    };
+
+    match (step, star) {
+      (Some(len), _) => { pi += len; ni += 1; },
+      (None, Some((after, taken))) => {
+        star = Some((after, taken + 1));
+        pi = after;
+        ni = taken + 1;
+      },
+      (None, None) => { return false; }
+    }
+  }
+  p[pi..].iter().all(|c| *c == '*')
+}
+
+// a leading dot is only matched by name, so wildcards skip hidden files
+pub fn matches(pattern: &str, name: &str) -> bool {
+  if name.starts_with('.') && !pattern.starts_with('.') {
+    return false;
+  }
+  match_here(&pattern.chars().collect::<Vec<char>>(), &name.chars().collect::<Vec<char>>())
+}
+
+fn unescape(component: &str) -> String {
+  let mut out = String::new();
+  let mut chars = component.chars();
+  while let Some(c) = chars.next() {
+    out.push(if c == '\\' { chars.next().unwrap_or('\\') } else { c });
+  }
+  out
+}
+
+fn entries(dir: &Path) -> Vec<(String, PathBuf)> {
+  let read = std::fs::read_dir(if dir.as_os_str().is_empty() { Path::new(".") } else { dir });
+  match read {
+    Ok(es) => es.filter_map(|e| e.ok()).filter_map(|e| Some((e.file_name().into_string().ok()?, dir.join(e.file_name())))).collect(),
+    Err(_) => Vec::new()
+  }
+}
+
+fn walk(base: PathBuf, components: &[&str], out: &mut Vec<PathBuf>) {
+  let Some((component, rest)) = components.split_first() else {
+    out.push(base);
+    return;
+  };
+
+  if *component == "**" {
+    // any number of directories, including none; last, it gives everything beneath
+    if !rest.is_empty() {
+      walk(base.clone(), rest, out);
+    }
+    for (name, path) in entries(&base) {
+      if name.starts_with('.') {
+        continue;
+      }
+      if rest.is_empty() {
+        out.push(path.clone());
+      }
+      if path.is_dir() && !path.is_symlink() {
+        walk(path, components, out);
+      }
+    }
+  } else if has_magic(component) {
+    for (name, path) in entries(&base) {
+      if matches(component, &name) && (rest.is_empty() || path.is_dir()) {
+        walk(path, rest, out);
+      }
+    }
+  } else {
+    let path = base.join(unescape(component));
+    if rest.is_empty() && !path.exists() && !path.is_symlink() {
+      return;
+    }
+    walk(path, rest, out);
+  }
+}
+
+// matching paths in sorted order; a pattern which matches nothing gives nothing
+pub fn glob(pattern: &str) -> Vec<String> {
+  let mut out = Vec::new();
+  for pattern in expand_braces(pattern) {
+    let (base, rest) = match pattern.strip_prefix('/') {
+      Some(rest) => (PathBuf::from("/"), rest),
+      None => (PathBuf::new(), pattern.as_str())
+    };
+    let components = rest.split('/').filter(|c| !c.is_empty()).collect::<Vec<&str>>();
+
+    let mut found = Vec::new();
+    walk(base, &components, &mut found);
+    let mut found = found.into_iter().map(|p| p.to_string_lossy().into_owned()).collect::<Vec<String>>();
+    found.sort();
+    found.dedup();
+    out.extend(found);
+  }
+  out
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn wildcards() {
+    assert!(matches("*.rs", "glob.rs"));
+    assert!(!matches("*.rs", "glob.rs.bak"));
+    assert!(matches("a*b*c", "abc"));
+    assert!(matches("a*b*c", "axxbyyc"));
+    assert!(!matches("a*b*c", "axxbyy"));
+    assert!(matches("*", ""));
+    assert!(matches("?x*", "ax"));
+    assert!(!matches("?", ""));
+    assert!(matches("**a", "a"));
+  }
+
+  #[test]
+  fn classes_and_escapes() {
+    assert!(matches("[a-c]*", "banana"));
+    assert!(!matches("[!a-c]*", "banana"));
+    assert!(matches("*[0-9]", "file7"));
+    assert!(matches("[x", "[x"));
+    assert!(matches("\\*", "*"));
+    assert!(!matches("\\*", "a"));
+    assert!(matches("a*\\?", "abc?"));
+  }
+
+  #[test]
+  fn hidden_files() {
+    assert!(!matches("*", ".hidden"));
+    assert!(matches(".*", ".hidden"));
+  }
+
+  #[test]
+  fn many_stars_stay_fast() {
+    let name = "a".repeat(60);
+    let pattern = "a*".repeat(30) + "b";
+    assert!(!matches(&pattern, &name));
+    assert!(matches(&("a*".repeat(30) + "a"), &name));
+  }
+}
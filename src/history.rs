@@ -0,0 +1,72 @@
+use std::env;
+use std::path::PathBuf;
+
+const HISTORY_FILE: &str = ".brie_history";
+const HISTORY_LIMIT: usize = 1000;
+
+fn path() -> Option<PathBuf> {
+  env::var_os("HOME").map(|h| PathBuf::from(h).join(HISTORY_FILE))
+}
+
+// one entry per line, so newlines and the escape itself are escaped
+fn escape(s: &str) -> String {
+  s.replace("\\", "\\\\").replace("\n", "\\n")
+}
+
+fn unescape(s: &str) -> String {
+  let mut out = String::new();
+  let mut chars = s.chars();
+  while let Some(c) = chars.next() {
+    if c == '\\' {
+      match chars.next() {
+        Some('n') => out.push('\n'),
+        Some(x) => out.push(x),
+        None => out.push('\\')
+      }
+    } else {
+      out.push(c);
+    }
+  }
+  out
+}
+
+pub fn load() -> Vec<String> {
+  match path().and_then(|p| std::fs::read_to_string(p).ok()) {
+    Some(s) => s.lines().filter(|l| !l.is_empty()).map(unescape).collect(),
+    None => Vec::new()
+  }
+}
+
+pub fn save(history: &[String]) -> Result<(), String> {
+  let p = path().ok_or("Cannot save history; $HOME is not set.".to_string())?;
+  let start = history.len().saturating_sub(HISTORY_LIMIT);
+  let text = history[start..].iter().map(|h| escape(h) + "\n").collect::<String>();
+  std::fs::write(&p, text).map_err(|e| format!("Failed to write history to {}.  Trace: {}", p.display(), e))
+}
+
+// settle the line just entered: blanks are dropped, and an earlier copy gives way to the newest
+pub fn commit(history: &mut Vec<String>) -> bool {
+  match history.pop() {
+    Some(line) if !line.trim().is_empty() => {
+      history.retain(|h| *h != line);
+      history.push(line);
+      if history.len() > HISTORY_LIMIT {
+        history.drain(..history.len() - HISTORY_LIMIT);
+      }
+      true
+    },
+    _ => false
+  }
+}
+
+pub fn find(history: &[String], query: &str, from: usize, backward: bool) -> Option<usize> {
+  if query.is_empty() {
+    return None;
+  }
+
+  if backward {
+    (0..=from.min(history.len().checked_sub(1)?)).rev().find(|i| history[*i].contains(query))
+  } else {
+    (from..history.len()).find(|i| history[*i].contains(query))
+  }
+}
@@ -0,0 +1,189 @@
+use crate::error::Error;
+use crate::eval;
+use crate::parse::AST;
+use crate::signals;
+
+use std::cell::Cell;
+use std::sync::{Condvar, Mutex};
+use std::thread::JoinHandle;
+
+pub enum JobStatus {
+  Running,
+  Stopped, // until fg or bg continues it
+  Done(Result<AST, Error>)
+}
+
+pub struct Job {
+  pub id: usize,
+  pub line: String,
+  pub status: JobStatus,
+  pids: Vec<u32>, // those not yet waited on, so never reused by another process
+  group: Option<i32>, // of the pipeline it is running now
+  foreground: bool,
+  handle: Option<JoinHandle<()>>,
+  notified: bool
+}
+
+// the table outlives any one line, so it cannot live in the evaluator's arguments
+static JOBS: Mutex<Vec<Job>> = Mutex::new(Vec::new());
+// signalled whenever a job stops or finishes
+static CHANGED: Condvar = Condvar::new();
+
+thread_local! {
+  static CURRENT_JOB: Cell<Option<usize>> = const { Cell::new(None) };
+}
+
+fn table() -> std::sync::MutexGuard<'static, Vec<Job>> {
+  JOBS.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+pub fn spawn<F: FnOnce() -> Result<AST, Error> + Send + 'static>(line: String, f: F) -> usize {
+  let id = {
+    let mut jobs = table();
+    let id = jobs.iter().map(|j| j.id).max().unwrap_or(0) + 1;
+    jobs.push(Job { id, line, status: JobStatus::Running, pids: Vec::new(), group: None, foreground: false, handle: None, notified: false });
+    id
+  };
+
+  let handle = std::thread::Builder::new().stack_size(eval::STACK_SIZE).spawn(move || {
+    CURRENT_JOB.with(|c| c.set(Some(id)));
+    let res = f();
+    if let Some(j) = table().iter_mut().find(|j| j.id == id) {
+      j.status = JobStatus::Done(res);
+    }
+    CHANGED.notify_all();
+  }).expect("failed to spawn job thread");
+
+  if let Some(j) = table().iter_mut().find(|j| j.id == id) {
+    j.handle = Some(handle);
+  }
+
+  id
+}
+
+// called for every spawned process; only processes started by a job's thread are recorded
+pub fn register(pid: u32) {
+  if let Some(id) = CURRENT_JOB.with(|c| c.get()) {
+    if let Some(j) = table().iter_mut().find(|j| j.id == id) {
+      j.pids.push(pid);
+    }
+  }
+}
+
+fn current<T>(f: impl FnOnce(&mut Job) -> T) -> Option<T> {
+  let id = CURRENT_JOB.with(|c| c.get())?;
+  table().iter_mut().find(|j| j.id == id).map(f)
+}
+
+// a process of the current job has been waited on
+pub fn reaped(pid: u32) {
+  current(|j| j.pids.retain(|p| *p != pid));
+}
+
+// a process of the current job has stopped, as on Ctrl-Z or reading the terminal from the background
+pub fn stopped() {
+  current(|j| j.status = JobStatus::Stopped);
+  CHANGED.notify_all();
+}
+
+// each pipeline of a job has a group of its own; gives whether the job holds the terminal
+pub fn set_group(group: i32) -> bool {
+  current(|j| { j.group = Some(group); j.foreground }).unwrap_or(false)
+}
+
+pub fn in_job() -> bool {
+  CURRENT_JOB.with(|c| c.get()).is_some()
+}
+
+pub fn latest() -> Option<usize> {
+  table().iter().map(|j| j.id).max()
+}
+
+pub fn resume(id: usize) -> bool {
+  match table().iter_mut().find(|j| j.id == id) {
+    Some(j) => {
+      if let JobStatus::Stopped = j.status {
+        j.status = JobStatus::Running;
+        for pid in &j.pids {
+          unsafe { libc::kill(*pid as libc::pid_t, libc::SIGCONT); }
+        }
+      }
+      true
+    },
+    None => false
+  }
+}
+
+// hands the job the terminal, and Ctrl-C with it, until it finishes or stops; gives whether it finished
+pub fn foreground(id: usize) -> Option<bool> {
+  let group = {
+    let mut jobs = table();
+    let j = jobs.iter_mut().find(|j| j.id == id)?;
+    j.foreground = true;
+    j.group
+  };
+  if let Some(g) = group {
+    signals::give_terminal(g);
+  }
+  resume(id);
+
+  let mut jobs = table();
+  let finished = loop {
+    match jobs.iter().find(|j| j.id == id).map(|j| &j.status) {
+      Some(JobStatus::Running) => { jobs = CHANGED.wait(jobs).unwrap_or_else(|e| e.into_inner()); },
+      Some(JobStatus::Stopped) => break false,
+      _ => break true
+    }
+  };
+  if let Some(j) = jobs.iter_mut().find(|j| j.id == id) {
+    j.foreground = false;
+  }
+  drop(jobs);
+
+  signals::give_terminal(0);
+  Some(finished)
+}
+
+// blocks until the job finishes or stops.  a finished job is dropped from the table and its thread joined;
+// a stopped one is left for fg or bg, as its thread would never finish
+pub fn wait(id: usize) -> Option<JobStatus> {
+  let mut jobs = table();
+  loop {
+    match jobs.iter().find(|j| j.id == id)?.status {
+      JobStatus::Running => { jobs = CHANGED.wait(jobs).unwrap_or_else(|e| e.into_inner()); },
+      JobStatus::Stopped => return Some(JobStatus::Stopped),
+      JobStatus::Done(_) => break
+    }
+  }
+  let i = jobs.iter().position(|j| j.id == id)?;
+  let job = jobs.remove(i);
+  drop(jobs);
+
+  if let Some(h) = job.handle {
+    let _ = h.join();
+  }
+  Some(job.status)
+}
+
+pub fn list() -> Vec<(usize, &'static str, String)> {
+  table().iter().map(|j| (j.id, match j.status {
+    JobStatus::Running => "running",
+    JobStatus::Stopped => "stopped",
+    JobStatus::Done(_) => "done"
+  }, j.line.clone())).collect()
+}
+
+// completion notices for jobs finished since the last call
+pub fn notices() -> Vec<String> {
+  let mut out = Vec::new();
+  for j in table().iter_mut() {
+    if j.notified { continue; }
+    match &j.status {
+      JobStatus::Done(Ok(code)) => out.push(format!("[{}] Done ({})  {}", j.id, code, j.line.trim())),
+      JobStatus::Done(Err(e)) => out.push(format!("[{}] Failed  {}\n\r{}", j.id, j.line.trim(), e)),
+      JobStatus::Running | JobStatus::Stopped => { continue; }
+    };
+    j.notified = true;
+  }
+  out
+}
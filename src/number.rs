@@ -0,0 +1,208 @@
+use crate::bigint::BigInt;
+
+use std::cmp::Ordering;
+use std::fmt;
+
+// integers are exact, growing into big integers rather than overflowing; anything else is a 64-bit float.
+// a big integer is only ever one which does not fit in an i64
+#[derive(Debug, Clone)]
+pub enum Number {
+  Int(i64),
+  Big(BigInt),
+  Float(f64)
+}
+
+fn from_big(b: BigInt) -> Number {
+  match b.to_i64() {
+    Some(i) => Number::Int(i),
+    None => Number::Big(b)
+  }
+}
+
+impl Number {
+  // integers parse exactly however long they are; anything else as a float
+  pub fn parse(s: &str) -> Option<Number> {
+    match BigInt::parse(s) {
+      Some(b) => Some(from_big(b)),
+      None => s.parse::<f64>().ok().map(Number::Float)
+    }
+  }
+
+  fn to_big(&self) -> Option<BigInt> {
+    match self {
+      Number::Int(i) => Some(BigInt::from_i64(*i)),
+      Number::Big(b) => Some(b.clone()),
+      Number::Float(_) => None
+    }
+  }
+
+  pub fn to_f64(&self) -> f64 {
+    match self {
+      Number::Int(i) => *i as f64,
+      Number::Big(b) => b.to_f64(),
+      Number::Float(f) => *f
+    }
+  }
+
+  // a whole number small enough for counts and indices, including a float with nothing after the point
+  pub fn as_i64(&self) -> Option<i64> {
+    match self {
+      Number::Int(i) => Some(*i),
+      Number::Big(_) => None,
+      Number::Float(f) if f.fract() == 0.0 && *f >= i64::MIN as f64 && *f < i64::MAX as f64 => Some(*f as i64),
+      Number::Float(_) => None
+    }
+  }
+
+  pub fn is_zero(&self) -> bool {
+    match self {
+      Number::Int(i) => *i == 0,
+      Number::Big(b) => b.is_zero(),
+      Number::Float(f) => *f == 0.0
+    }
+  }
+
+  pub fn neg(&self) -> Number {
+    match self {
+      Number::Int(i) => i.checked_neg().map(Number::Int).unwrap_or_else(|| from_big(BigInt::from_i64(*i).neg())),
+      Number::Big(b) => from_big(b.neg()),
+      Number::Float(f) => Number::Float(-f)
+    }
+  }
+
+  // integers stay exact, on overflow too; a float on either side makes a float
+  fn exact(&self, r: &Number, small: fn(i64, i64) -> Option<i64>, big: fn(&BigInt, &BigInt) -> BigInt, float: fn(f64, f64) -> f64) -> Number {
+    match (self, r) {
+      (Number::Int(l), Number::Int(r)) => match small(*l, *r) {
+        Some(i) => Number::Int(i),
+        None => from_big(big(&BigInt::from_i64(*l), &BigInt::from_i64(*r)))
+      },
+      (Number::Float(_), _) | (_, Number::Float(_)) => Number::Float(float(self.to_f64(), r.to_f64())),
+      _ => from_big(big(&self.to_big().unwrap(), &r.to_big().unwrap()))
+    }
+  }
+
+  pub fn add(&self, r: &Number) -> Number {
+    self.exact(r, i64::checked_add, BigInt::add, |l, r| l + r)
+  }
+
+  pub fn sub(&self, r: &Number) -> Number {
+    self.exact(r, i64::checked_sub, BigInt::sub, |l, r| l - r)
+  }
+
+  pub fn mul(&self, r: &Number) -> Number {
+    self.exact(r, i64::checked_mul, BigInt::mul, |l, r| l * r)
+  }
+
+  // integers which divide evenly give an integer; otherwise, or by zero, a float
+  pub fn div(&self, r: &Number) -> Number {
+    if let (Some(lb), Some(rb)) = (self.to_big(), r.to_big()) {
+      if let Some((q, m)) = lb.divrem(&rb) {
+        if m.is_zero() {
+          return from_big(q);
+        }
+      }
+    }
+    Number::Float(self.to_f64() / r.to_f64())
+  }
+}
+
+impl From<i64> for Number {
+  fn from(i: i64) -> Number {
+    Number::Int(i)
+  }
+}
+
+impl From<f64> for Number {
+  fn from(f: f64) -> Number {
+    Number::Float(f)
+  }
+}
+
+impl From<bool> for Number {
+  fn from(b: bool) -> Number {
+    Number::Int(b as i64)
+  }
+}
+
+// by value, so 2 and 2.0 are equal
+impl PartialEq for Number {
+  fn eq(&self, r: &Number) -> bool {
+    self.partial_cmp(r) == Some(Ordering::Equal)
+  }
+}
+
+impl PartialOrd for Number {
+  fn partial_cmp(&self, r: &Number) -> Option<Ordering> {
+    match (self, r) {
+      (Number::Int(l), Number::Int(r)) => Some(l.cmp(r)),
+      (Number::Float(_), _) | (_, Number::Float(_)) => self.to_f64().partial_cmp(&r.to_f64()),
+      _ => Some(self.to_big().unwrap().cmp(&r.to_big().unwrap()))
+    }
+  }
+}
+
+impl fmt::Display for Number {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      Number::Int(i) => write!(f, "{}", i),
+      Number::Big(b) => write!(f, "{}", b),
+      Number::Float(x) => write!(f, "{}", x)
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn n(s: &str) -> Number {
+    Number::parse(s).unwrap()
+  }
+
+  #[test]
+  fn parse_and_print() {
+    assert!(matches!(n("42"), Number::Int(42)));
+    assert!(matches!(n("-9223372036854775808"), Number::Int(i64::MIN)));
+    assert!(matches!(n("9223372036854775808"), Number::Big(_)));
+    assert!(matches!(n("1.5"), Number::Float(_)));
+    assert!(matches!(n("1e3"), Number::Float(_)));
+    assert_eq!(n("-123456789012345678901234567890").to_string(), "-123456789012345678901234567890");
+    assert_eq!(n("2.5").to_string(), "2.5");
+    assert!(Number::parse("x").is_none());
+  }
+
+  #[test]
+  fn overflow_grows_and_shrinks_back() {
+    let max = Number::from(i64::MAX);
+    assert_eq!(max.add(&Number::from(1)).to_string(), "9223372036854775808");
+    assert!(matches!(max.add(&Number::from(1)).sub(&Number::from(1)), Number::Int(i64::MAX)));
+    assert_eq!(Number::from(i64::MIN).sub(&Number::from(1)).to_string(), "-9223372036854775809");
+    assert_eq!(Number::from(i64::MIN).neg().to_string(), "9223372036854775808");
+    assert_eq!(max.mul(&max).to_string(), "85070591730234615847396907784232501249");
+    assert_eq!(Number::from(-4).mul(&Number::from(i64::MAX)).to_string(), "-36893488147419103228");
+  }
+
+  #[test]
+  fn floats_make_floats() {
+    assert!(matches!(n("1").add(&n("0.5")), Number::Float(f) if f == 1.5));
+    assert!(matches!(n("99999999999999999999").mul(&n("2.0")), Number::Float(_)));
+  }
+
+  #[test]
+  fn div_is_exact_only_when_even() {
+    assert!(matches!(n("6").div(&n("3")), Number::Int(2)));
+    assert!(matches!(n("-7").div(&n("2")), Number::Float(f) if f == -3.5));
+    assert_eq!(n("100000000000000000000000").div(&n("-1000")).to_string(), "-100000000000000000000");
+    assert!(matches!(n("1").div(&n("0")), Number::Float(f) if f.is_infinite()));
+  }
+
+  #[test]
+  fn compare_across_kinds() {
+    assert_eq!(n("2"), n("2.0"));
+    assert!(n("-99999999999999999999") < n("-1"));
+    assert!(n("99999999999999999999") > n("1e19"));
+    assert!(n("9223372036854775807") < n("9223372036854775808"));
+    assert_eq!(n("1").partial_cmp(&Number::Float(f64::NAN)), None);
+  }
+}
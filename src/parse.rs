@@ -1,37 +1,49 @@
 use crate::charset::CHARSET;
 use crate::token::{self, Token};
-use crate::error::{location_format, noteformat, error};
+use crate::error::{location_format, noteformat, error, Error, ErrorKind};
+use crate::number::Number;
+use crate::scope::Env;
 
-use std::collections::HashMap;
 use std::fmt;
 use unicode_width::UnicodeWidthStr;
 
-#[derive(Clone, Copy, PartialEq, Debug)]
+#[derive(Clone, PartialEq, Debug)]
 pub enum ToRedirect {
   ToStdout,
   ToStderr,
   ToBoth,
-  ToNull
+  ToNull,
+  File(String), // truncate
+  Append(String)
 }
 
-#[derive(Clone, Copy, PartialEq, Debug)]
+#[derive(Clone, PartialEq, Debug)]
 pub struct Redirect {
   pub stdout: ToRedirect,
-  pub stderr: ToRedirect
+  pub stderr: ToRedirect,
+  pub stdin: Option<String>, // file
+  pub env: Vec<(String, String)> // variables set for this process alone
 }
 
 #[derive(Clone, Debug)]
 pub enum ASTVal {
-  Assign(String, Box<AST>), // may not nest, via grammar
-  AliasAssign(String, Box<AST>), // same
-  Num(f32),
+  Assign(String, Box<AST>, bool), // may not nest, via grammar; the flag marks a write to an outer scope
+  AliasAssign(String, Box<AST>, bool), // same
+  Num(Number),
+  Bool(bool),
+  Null,
   Symbol(String),
   Ident(String),
   String(String),
   Apply(Option<Box<AST>>, Box<AST>, Option<Box<AST>>),
   Array(Vec<AST>),
+  Dict(Vec<(String, AST)>), // keys in the order they were first given
+  Table(Vec<String>, Vec<Vec<AST>>), // column names, then the rows, each as long as the names
   Operator(Box<AST>, String, Box<AST>),
   Command(String, Vec<AST>, Box<AST>, Redirect), // name, args, stdin, redir
+  Job(usize), // id in the job table
+  Lambda(Option<String>, String, Box<AST>, Option<Env>), // left name, right name, body, and the scopes it was written in once resolved
+  Block(Vec<AST>), // the statements of a function body written over several lines
   SymbolList(Vec<String>) // never seen by eval
 }
 
@@ -44,7 +56,8 @@ impl PartialEq for ASTVal {
 #[derive(Debug, Clone, Copy)]
 pub struct Location {
   pub col: usize,
-  pub len: usize
+  pub len: usize,
+  pub line: usize // within the statement, which may span several lines
 }
 
 #[derive(Clone)]
@@ -66,59 +79,88 @@ impl PartialEq for AST {
   }
 }
 
-pub fn respect_fill(node: &AST, env: &HashMap<String, AST>) -> bool {
+pub fn respect_fill(node: &AST, env: &Env) -> bool {
   match &node.variant {
     ASTVal::Operator(_, o, _) if o == CHARSET::Reduce.as_str() || o == CHARSET::Scan.as_str() || o == CHARSET::Where.as_str() => false,
     ASTVal::Operator(f, _, _) => respect_fill(f, env),
     ASTVal::Apply(_, _, r) if r.is_some() && is_fn(r.as_ref().unwrap(), env) => respect_fill(&r.as_ref().unwrap(), env),
-    ASTVal::Ident(x) => if let Some(id) = env.get(x) { respect_fill(id, env) } else if which::which(x).is_ok() { true } else { false },
+    ASTVal::Ident(x) => if let Some(id) = env.get(x) { respect_fill(&id, env) } else if which::which(x).is_ok() { true } else { false },
     _ => false
   }
 }
 
-pub fn fill_from_right(node: &AST, env: &HashMap<String, AST>) -> bool {
+pub fn fill_from_right(node: &AST, env: &Env) -> bool {
   match &node.variant {
     ASTVal::Operator(_, o, _) if o == CHARSET::Reduce.as_str() || o == CHARSET::Scan.as_str() || o == CHARSET::Where.as_str() || o == CHARSET::Iterate.as_str() => true,
     ASTVal::Operator(f, _, _) => fill_from_right(f, env),
     ASTVal::Apply(_, _, r) if r.is_some() && is_fn(r.as_ref().unwrap(), env) => fill_from_right(&r.as_ref().unwrap(), env),
     ASTVal::Apply(l, _, r) => !(r.is_some() && l.is_none()),
-    ASTVal::Ident(i) => if let Some(id) = env.get(i) { fill_from_right(id, env) } else { true },
+    ASTVal::Ident(i) => if let Some(id) = env.get(i) { fill_from_right(&id, env) } else { true },
     _ => true
   }
 }
 
-pub fn is_fn(node: &AST, env: &HashMap<String, AST>) -> bool {
+pub fn is_fn(node: &AST, env: &Env) -> bool {
   stricter_is_fn(node, env) || (match &node.variant {
+    // a fn of one name takes no left argument, so given a right one it is a call rather than a partial application
+    ASTVal::Apply(None, f, Some(_)) if is_monadic(f, env) => false,
+    // so does try, with or without a handler before it
+    ASTVal::Apply(None, f, Some(_)) if is_try(f) => false,
     ASTVal::Apply(l, _, r) => l.is_none() || r.is_none(),
     _ => false
   })
 }
 
+fn is_monadic(f: &AST, env: &Env) -> bool {
+  match &f.variant {
+    ASTVal::Lambda(None, ..) => true,
+    ASTVal::Apply(None, f, None) => is_monadic(f, env),
+    ASTVal::Ident(i) => env.get(i).is_some_and(|v| is_monadic(&v, env)),
+    _ => false
+  }
+}
+
+// try alone, or with a function before it to handle the failure, as in `f try`
+pub fn is_try(f: &AST) -> bool {
+  match &f.variant {
+    ASTVal::Ident(x) => x == "try",
+    ASTVal::Apply(None, f, None) => is_try(f),
+    ASTVal::Apply(None, _, Some(r)) => matches!(&r.variant, ASTVal::Ident(x) if x == "try"),
+    _ => false
+  }
+}
+
 pub fn is_lazy(f: &AST) -> bool {
+  // an argument which has been given its right argument is a call, and is evaluated as usual wherever it is nested
+  let part = |a: &AST| !matches!(a.variant, ASTVal::Apply(_, _, Some(_))) && is_lazy(a);
   match &f.variant {
     ASTVal::Operator(f, _, _) => is_lazy(f),
-    ASTVal::Ident(x) if x == "pipe" => true,
+    ASTVal::Ident(x) if x == "pipe" || x == "withenv" || x == "try" || x == "if" || x == "else" => true,
     ASTVal::Apply(x, y, z) if
-         x.as_ref().and_then(|x| is_lazy(x).then(|| ())).is_some()
+         x.as_ref().is_some_and(|x| part(x))
       || is_lazy(y)
-      || z.as_ref().and_then(|z| is_lazy(z).then(|| ())).is_some()
+      || z.as_ref().is_some_and(|z| part(z))
         => true,
     
     _ => false
   }
 }
 
-pub fn stricter_is_fn(node: &AST, env: &HashMap<String, AST>) -> bool {
+pub fn stricter_is_fn(node: &AST, env: &Env) -> bool {
   (match &node.variant {
-    ASTVal::Operator(..) => true,
-    ASTVal::Ident(i) => token::in_operators(&i) || if let Some(v) = env.get(i) { is_fn(v, env) } else { which::which(i).is_ok() /* filecommand */ },
+    ASTVal::Operator(..) | ASTVal::Lambda(..) => true,
+    ASTVal::Ident(i) => token::in_operators(&i) || if let Some(v) = env.get(i) { is_fn(&v, env) } else { which::which(i).is_ok() /* filecommand */ },
     _ => false
   }) || is_train(node, env)
 }
 
-fn is_train(node: &AST, env: &HashMap<String, AST>) -> bool {
+fn is_train(node: &AST, env: &Env) -> bool {
   match &node.variant {
     ASTVal::Apply(None, f, None) => is_train(f, env),
+    // try given what to evaluate is a call, whatever that looks like
+    ASTVal::Apply(_, f, Some(_)) if is_try(f) => false,
+    // and so are the branches given to else
+    ASTVal::Apply(Some(_), f, Some(_)) if matches!(&f.variant, ASTVal::Ident(x) if x == "else") => false,
     ASTVal::Apply(_l, _f, r) => r.as_ref().and_then(|r| is_fn(&*r, env).then(|| ())).is_some(),
     _ => false
   }
@@ -130,15 +172,15 @@ struct ParseRes {
   is_f: bool
 }
 
-fn parse_atom(toks: &mut Vec<Token>, env: &HashMap<String, AST>, s: &String) -> Result<ParseRes, String> {
+fn parse_atom(toks: &mut Vec<Token>, env: &Env, s: &String) -> Result<ParseRes, Error> {
   if toks.len() == 0 {
-    Err(error(s, 0, 0, "Unexpected EOF parsing expression.", "This is an internal error."))
+    Err(error(ErrorKind::Syntax, s, Location { col: 0, len: 0, line: 0 }, "Unexpected EOF parsing expression.", "This is an internal error."))
   }
   
   else if toks[0].is_num() {
     let tok = toks.remove(0);
     Ok(ParseRes {
-      v: AST { variant: ASTVal::Num(tok.val.parse::<f32>().map_err(|_| error(s, tok.col, tok.val.width(), "Invalid numeric literal.", noteformat!("Found {}.", tok.val)))?), location: Location { col: tok.col, len: tok.val.width()} },
+      v: AST { variant: ASTVal::Num(Number::parse(&tok.val).ok_or_else(|| error(ErrorKind::Syntax, s, tok.location(), "Invalid numeric literal.", noteformat!("Found {}.", tok.val)))?), location: tok.location() },
       is_f: tok.followed
    })
   }
@@ -158,41 +200,170 @@ fn parse_atom(toks: &mut Vec<Token>, env: &HashMap<String, AST>, s: &String) ->
 
         ASTVal::SymbolList(res)
       },
-      location: Location { col: tok.col, len: w }
+      location: Location { col: tok.col, len: w, line: tok.line }
     }, is_f: tok.followed })
   }
 
+  else if toks[0].val == "fn" {
+    parse_lambda(toks, env, s)
+  }
+
+  else if toks[0].val == token::DICT_OPEN {
+    parse_dict(toks, env, s)
+  }
+
   else if toks[0].is_id(){
     let tok = toks.remove(0);
     let len = tok.val.width();
-    Ok(ParseRes { v: AST { variant: ASTVal::Ident(tok.val.to_string()), location: Location { col: tok.col, len } }, is_f: tok.followed })
+    Ok(ParseRes { v: AST { variant: ASTVal::Ident(tok.val.to_string()), location: Location { col: tok.col, len, line: tok.line } }, is_f: tok.followed })
   }
 
   else if toks[0].is_string() {
     let tok = toks.remove(0);
     let newchs = tok.val.chars().skip(1);
     
-    Ok(ParseRes { v: AST { variant: ASTVal::String(newchs.collect()), location: Location {col: tok.col, len: tok.val.width() } }, is_f: tok.followed })
+    Ok(ParseRes { v: AST { variant: ASTVal::String(newchs.collect()), location: tok.location() }, is_f: tok.followed })
   }
 
   else if toks[0].val == "(" {
     let opentok = toks.remove(0);
     let e = parse_command(toks, env, s);
     if toks.len() == 0 {
-      Err(error(s, 0, 0, "Expecting close parenthesis.", noteformat!("Open parenthesis found here:\n\r{}", location_format(s, opentok.col, opentok.val.width()))))
+      Err(error(ErrorKind::Syntax, s, Location { col: 0, len: 0, line: 0 }, "Expecting close parenthesis.", noteformat!("Open parenthesis found here:\n\r{}", location_format(s, opentok.location()))))
     } else if toks[0].val != ")" {
-      Err(error(s, toks[0].col, toks[0].val.width(), "Expecting close parenthesis.", noteformat!("Open parenthesis found here:\n\r{}", location_format(s, opentok.col, opentok.val.width()))))
+      Err(error(ErrorKind::Syntax, s, toks[0].location(), "Expecting close parenthesis.", noteformat!("Open parenthesis found here:\n\r{}", location_format(s, opentok.location()))))
     } else {
       Ok(ParseRes { v: e?, is_f: toks.remove(0).followed })
     }
   }
 
   else {
-    Err(error(s, toks[0].col, toks[0].val.width(), "Expected expression.", "This is an internal error."))
+    Err(error(ErrorKind::Syntax, s, toks[0].location(), "Expected expression.", "This is an internal error."))
   }
 }
 
-fn parse_array(toks: &mut Vec<Token>, env: &HashMap<String, AST>, s: &String) -> Result<Option<ParseRes>, String> {
+fn valid_name(tok: &Token) -> bool {
+  let zch = tok.val.chars().next().unwrap();
+  (zch.is_alphabetic() || zch == '_') && !token::in_operators(&tok.val) && tok.val != "fn" && tok.val != "outer"
+}
+
+// fn (body) names its arguments x and y; fn a (body) names the right one a; fn a b (body) names both
+fn parse_lambda(toks: &mut Vec<Token>, env: &Env, s: &String) -> Result<ParseRes, Error> {
+  let fntok = toks.remove(0);
+
+  let mut names = Vec::new();
+  while !toks.is_empty() && toks[0].val != "(" {
+    if !valid_name(&toks[0]) || names.len() == 2 {
+      return Err(error(ErrorKind::Syntax, s, toks[0].location(), "Expected a parameter name or the function body.", "A function is written as fn, up to two parameter names, and a parenthesized body, as in `fn a b (a + b)`."));
+    }
+    names.push(toks.remove(0).val);
+  }
+
+  if toks.is_empty() {
+    return Err(error(ErrorKind::Syntax, s, fntok.location(), "Expected the function body.", "A function is written as fn, up to two parameter names, and a parenthesized body, as in `fn a b (a + b)`."));
+  }
+
+  let (lname, rname) = match names.len() {
+    0 => (Some("x".to_string()), "y".to_string()),
+    1 => (None, names.remove(0)),
+    _ => (Some(names.remove(0)), names.remove(0))
+  };
+
+  // parameters are values within the body, whatever they name outside it
+  let mut local = env.child();
+  for n in lname.iter().chain(Some(&rname)) {
+    local.insert(n.to_string(), AST { variant: ASTVal::Array(Vec::new()), location: fntok.location() });
+  }
+
+  let statements = body_statements(toks);
+  if statements.len() < 2 {
+    let body = parse_atom(toks, &local, s)?;
+    return Ok(ParseRes { v: AST { variant: ASTVal::Lambda(lname, rname, Box::new(body.v), None), location: fntok.location() }, is_f: body.is_f });
+  }
+
+  // a body over several lines holds one statement per line, and may assign
+  let open = toks.remove(0);
+  toks.drain(..statements.iter().map(|st| st.len()).sum::<usize>() + 1);
+  let mut block = Vec::new();
+  for mut st in statements {
+    if let Some(v) = parse_commands(&mut st, &local, s)? {
+      // later statements parse knowing what earlier ones bound; `;` evaluates, so only a fn stays a function
+      match &v.variant {
+        ASTVal::Assign(n, v, _) if matches!(strip_apply(*v.clone()).variant, ASTVal::Lambda(..)) => local.insert(n.to_string(), strip_apply(*v.clone())),
+        ASTVal::Assign(n, ..) => local.insert(n.to_string(), AST { variant: ASTVal::Array(Vec::new()), location: v.location }),
+        ASTVal::AliasAssign(n, v, _) => local.insert(n.to_string(), *v.clone()),
+        _ => ()
+      }
+      block.push(v);
+    }
+  }
+
+  Ok(ParseRes { v: AST { variant: ASTVal::Lambda(lname, rname, Box::new(AST { variant: ASTVal::Block(block), location: open.location() }), None), location: fntok.location() }, is_f: false })
+}
+
+// {name: value, "a key": value}, each value an atom, as in an array; a key given twice keeps its first place and its last value
+fn parse_dict(toks: &mut Vec<Token>, env: &Env, s: &String) -> Result<ParseRes, Error> {
+  let opentok = toks.remove(0);
+  let open = Location { col: opentok.col, len: 1, line: opentok.line };
+  let expected = |toks: &Vec<Token>, msg: &str| {
+    let loc = toks.first().map(|t| t.location()).unwrap_or(open);
+    error(ErrorKind::Syntax, s, loc, msg, noteformat!("A dict is written as keys and values in braces, as in `{{name: \"brie\", size: 3}}`.  The dict opens here:\n\r{}", location_format(s, open)))
+  };
+
+  let mut es: Vec<(String, AST)> = Vec::new();
+  loop {
+    match toks.first() {
+      Some(t) if t.val == CHARSET::RTack && es.is_empty() => break,
+      Some(t) if t.is_string() || t.val.starts_with(|c: char| c.is_alphabetic() || c == '_') => {},
+      _ => return Err(expected(toks, "Expected a dict key."))
+    }
+    let keytok = toks.remove(0);
+    let key = if keytok.is_string() { keytok.val.chars().skip(1).collect() } else { keytok.val };
+
+    if toks.first().is_none_or(|t| t.val != CHARSET::Take) {
+      return Err(expected(toks, "Expected a colon after the key."));
+    }
+    toks.remove(0);
+    if toks.first().is_none_or(|t| t.val == CHARSET::RTack || t.val == CHARSET::Concat || !token::more_there(toks)) {
+      return Err(expected(toks, "Expected a value for the key."));
+    }
+    let v = parse_atom(toks, env, s)?.v;
+    match es.iter_mut().find(|(k, _)| *k == key) {
+      Some(e) => e.1 = v,
+      None => es.push((key, v))
+    }
+
+    match toks.first() {
+      Some(t) if t.val == CHARSET::Concat => { toks.remove(0); },
+      Some(t) if t.val == CHARSET::RTack => break,
+      _ => return Err(expected(toks, "Expected a comma or a closing brace after the value."))
+    }
+  }
+
+  let closetok = toks.remove(0);
+  Ok(ParseRes { v: AST { variant: ASTVal::Dict(es), location: open }, is_f: closetok.followed })
+}
+
+// splits the parenthesized body at the front of toks into lines, leaving toks alone
+fn body_statements(toks: &[Token]) -> Vec<Vec<Token>> {
+  let mut statements: Vec<Vec<Token>> = Vec::new();
+  let mut depth = 0;
+  for tok in toks.iter().skip(1) {
+    if tok.val == ")" && depth == 0 {
+      return statements;
+    }
+    if depth == 0 && statements.last().and_then(|st| st.last()).is_none_or(|last| last.line != tok.line) {
+      statements.push(Vec::new());
+    }
+    if tok.val == "(" { depth += 1; }
+    if tok.val == ")" { depth -= 1; }
+    statements.last_mut().unwrap().push(tok.clone());
+  }
+  // unclosed; parse_atom reports it
+  Vec::new()
+}
+
+fn parse_array(toks: &mut Vec<Token>, env: &Env, s: &String) -> Result<Option<ParseRes>, Error> {
   let mut exprs = Vec::new();
 
   let mut anything_at_all = false;
@@ -208,7 +379,7 @@ fn parse_array(toks: &mut Vec<Token>, env: &HashMap<String, AST>, s: &String) ->
       match expr.v.variant {
         ASTVal::SymbolList(ss) => {
           for (i, s) in ss.iter().enumerate() {
-            exprs.push(ParseRes { v: AST { variant: ASTVal::Symbol(s.to_string()), location: Location { col: expr.v.location.col + i, len: 1 }}, is_f: expr.is_f });
+            exprs.push(ParseRes { v: AST { variant: ASTVal::Symbol(s.to_string()), location: Location { col: expr.v.location.col + i, len: 1, line: expr.v.location.line }}, is_f: expr.is_f });
           }
         },
         _ => exprs.push(expr)
@@ -221,7 +392,7 @@ fn parse_array(toks: &mut Vec<Token>, env: &HashMap<String, AST>, s: &String) ->
   if !anything_at_all {
     Ok(None)
   } else if exprs.len() == 0 {
-    Ok(Some(ParseRes { v: AST { variant: ASTVal::Array(Vec::new()), location: Location { col: 0, len: 0 }}, is_f: false }))
+    Ok(Some(ParseRes { v: AST { variant: ASTVal::Array(Vec::new()), location: Location { col: 0, len: 0, line: 0 }}, is_f: false }))
   } else if exprs.len() > 1 {
     let l = exprs[0].v.location;
     let is_f = exprs.last().unwrap().is_f;
@@ -231,7 +402,7 @@ fn parse_array(toks: &mut Vec<Token>, env: &HashMap<String, AST>, s: &String) ->
   }
 }
 
-fn parse_operator(toks: &mut Vec<Token>, env: &HashMap<String, AST>, s: &String) -> Result<ParseRes, String> {
+fn parse_operator(toks: &mut Vec<Token>, env: &Env, s: &String) -> Result<ParseRes, Error> {
   let mut expr = parse_atom(toks, env, s)?;
   
   while toks.len() > 0 && token::in_true_operators(&toks[0].val) {
@@ -244,12 +415,12 @@ fn parse_operator(toks: &mut Vec<Token>, env: &HashMap<String, AST>, s: &String)
     if toks.len() < 1 || toks[0].val != CHARSET::EndOperator {
       *toks = old_toks;
       lastfollowed = oper.followed;
-      rhs = AST { variant: ASTVal::Array(Vec::new()), location: Location { col: oper.col, len }};
+      rhs = AST { variant: ASTVal::Array(Vec::new()), location: Location { col: oper.col, len, line: oper.line }};
     } else {
       lastfollowed = toks.remove(0).followed;
     }
     
-    expr = ParseRes { v: AST { variant: ASTVal::Operator(Box::new(expr.v), oper.val.to_string(), Box::new(rhs)), location: Location { col: oper.col, len } }, is_f: lastfollowed };
+    expr = ParseRes { v: AST { variant: ASTVal::Operator(Box::new(expr.v), oper.val.to_string(), Box::new(rhs)), location: Location { col: oper.col, len, line: oper.line } }, is_f: lastfollowed };
   }
   
   Ok(expr)
@@ -262,7 +433,7 @@ pub fn fathometer(v: &AST) -> i32 {
   }
 }
 
-fn parse_train(toks: &mut Vec<Token>, env: &HashMap<String, AST>, s: &String) -> Result<AST, String> {
+fn parse_train(toks: &mut Vec<Token>, env: &Env, s: &String) -> Result<AST, Error> {
   let mut fns = Vec::new();
 
   let mut free_ride = false;
@@ -303,11 +474,11 @@ fn parse_train(toks: &mut Vec<Token>, env: &HashMap<String, AST>, s: &String) ->
   Ok(result)
 }
 
-fn parse_fcall(toks: &mut Vec<Token>, env: &HashMap<String, AST>, s: &String) -> Result<AST, String> {
+fn parse_fcall(toks: &mut Vec<Token>, env: &Env, s: &String) -> Result<AST, Error> {
   let lvals = parse_array(toks, env, s)?.map(|x| x.v);
 
   if !token::more_there(toks) {
-    return Ok(lvals.unwrap_or(AST { variant: ASTVal::Array(Vec::new()), location: Location { col: 0, len: 0 } }));
+    return Ok(lvals.unwrap_or(AST { variant: ASTVal::Array(Vec::new()), location: Location { col: 0, len: 0, line: 0 } }));
   }
   
   let fun = parse_train(toks, env, s)?;
@@ -325,7 +496,7 @@ fn parse_fcall(toks: &mut Vec<Token>, env: &HashMap<String, AST>, s: &String) ->
   })
 }
 
-fn parse_command(toks: &mut Vec<Token>, env: &HashMap<String, AST>, s: &String) -> Result<AST, String> {
+fn parse_command(toks: &mut Vec<Token>, env: &Env, s: &String) -> Result<AST, Error> {
   let mut f = parse_fcall(toks, env, s)?;
   
   while toks.len() > 0 && (toks[0].val == CHARSET::Pipe || toks[0].val == CHARSET::AntiPipe) {
@@ -338,11 +509,11 @@ fn parse_command(toks: &mut Vec<Token>, env: &HashMap<String, AST>, s: &String)
     let f2 = strip_apply(f2);
 
     if !is_fn(&f2, env) {
-      let (col, len) = if f2.location.len == 0 { (pipetok.col, pipetok.val.width()) } else { (f2.location.col, f2.location.col) };
-      Err(error(s, col, len, "Piping into non-function.", noteformat!("The following occupies a functional position, but is not a function:\n\r{}", f2)))?;
+      let l = if f2.location.len == 0 { pipetok.location() } else { f2.location };
+      Err(error(ErrorKind::Syntax, s, l, "Piping into non-function.", noteformat!("The following occupies a functional position, but is not a function:\n\r{}", f2)))?;
     }
     
-    f = AST { variant: ASTVal::Apply(Some(Box::new(f)), Box::new(f2), None), location: Location { col: pipetok.col, len: pipetok.val.width() } };
+    f = AST { variant: ASTVal::Apply(Some(Box::new(f)), Box::new(f2), None), location: pipetok.location() };
   }
   
   Ok(f)
@@ -355,8 +526,22 @@ fn strip_apply(node: AST) -> AST {
   }
 }
 
-pub fn parse_commands(toks: &mut Vec<Token>, env: &HashMap<String, AST>, s: &String) -> Result<Option<AST>, String> {
+// a function being named may call itself, so its name stands for a function of the same arity within its own body
+fn with_self(name: &str, toks: &[Token], env: &Env) -> Option<Env> {
+  if toks.first()?.val != "fn" {
+    return None;
+  }
+  let names = toks.iter().skip(1).take_while(|t| t.val != "(").count();
+  let l = toks[0].location();
+  let mut env = env.child();
+  env.insert(name.to_string(), AST { variant: ASTVal::Lambda((names != 1).then(|| "x".to_string()), "y".to_string(), Box::new(AST { variant: ASTVal::Array(Vec::new()), location: l }), None), location: l });
+  Some(env)
+}
+
+pub fn parse_commands(toks: &mut Vec<Token>, env: &Env, s: &String) -> Result<Option<AST>, Error> {
   let mut cmds = None;
+  // `outer name; value` writes to an enclosing scope rather than the current one
+  let outer = if toks.len() > 1 && toks[0].val == "outer" { Some(toks.remove(0)) } else { None };
   if toks.len() > 0 {
     let cmd = parse_command(toks, env, s);
     match cmd {
@@ -365,23 +550,25 @@ pub fn parse_commands(toks: &mut Vec<Token>, env: &HashMap<String, AST>, s: &Str
         if toks.len() > 0 && toks[0].val == CHARSET::Assign {
           if let ASTVal::Ident(i) = strip_apply(n).variant {
             let tok = toks.remove(0);
-            match parse_command(toks, env, s) {
+            match parse_command(toks, with_self(&i, toks, env).as_ref().unwrap_or(env), s) {
               Err(e) => { return Err(e); }
-              Ok(value) => cmds = Some(AST { variant: ASTVal::Assign(i, Box::new(value)), location: Location { col: tok.col, len: tok.val.width() } })
+              Ok(value) => cmds = Some(AST { variant: ASTVal::Assign(i, Box::new(value), outer.is_some()), location: tok.location() })
             }
           } else {
-            return Err(error(s, toks[0].col, toks[0].val.width(), "Identifier must be a valid name.", "A valid name consists of any alphabetic character or an underscore followed by any number of alphanumeric characters or underscores."));
+            return Err(error(ErrorKind::Syntax, s, toks[0].location(), "Identifier must be a valid name.", "A valid name consists of any alphabetic character or an underscore followed by any number of alphanumeric characters or underscores."));
           }
         } else if toks.len() > 0 && toks[0].val == CHARSET::Assign.as_str().repeat(2) {
           if let ASTVal::Ident(i) = strip_apply(n).variant {
             let tok = toks.remove(0);
-            match parse_command(toks, env, s) {
+            match parse_command(toks, with_self(&i, toks, env).as_ref().unwrap_or(env), s) {
               Err(e) => { return Err(e); }
-              Ok(value) => cmds = Some(AST { variant: ASTVal::AliasAssign(i, Box::new(value)), location: Location { col: tok.col, len: tok.val.width() } })
+              Ok(value) => cmds = Some(AST { variant: ASTVal::AliasAssign(i, Box::new(value), outer.is_some()), location: tok.location() })
             }
           } else {
-            return Err(error(s, toks[0].col, toks[0].val.width(), "Identifier must be a valid name.", "A valid name consists of any alphabetic character or an underscore followed by any number of alphanumeric characters or underscores."));
+            return Err(error(ErrorKind::Syntax, s, toks[0].location(), "Identifier must be a valid name.", "A valid name consists of any alphabetic character or an underscore followed by any number of alphanumeric characters or underscores."));
           }
+        } else if let Some(tok) = outer {
+          return Err(error(ErrorKind::Syntax, s, tok.location(), "Expected an assignment after outer.", "outer writes a name in an enclosing scope, as in `outer count; count + 1`."));
         } else {
           cmds = Some(n);
         }
@@ -389,7 +576,7 @@ pub fn parse_commands(toks: &mut Vec<Token>, env: &HashMap<String, AST>, s: &Str
     };
     
     if toks.len() > 0 {
-      return Err(error(s, toks[0].col, toks[0].val.width(), "Expected one command per line.", "Use newline to separate commands."));
+      return Err(error(ErrorKind::Syntax, s, toks[0].location(), "Expected one command per line.", "Use newline to separate commands."));
     }
   }
 
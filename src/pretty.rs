@@ -78,7 +78,7 @@ fn do_onetree(top: String, val: String) -> String {
 impl ASTVal {
   pub fn to_tree(&self) -> String {
     match self {
-      ASTVal::Ident(..) | ASTVal::Symbol(..) | ASTVal::Num(..) | ASTVal::String(..) => format!("{}", self),
+      ASTVal::Ident(..) | ASTVal::Symbol(..) | ASTVal::Num(..) | ASTVal::Bool(..) | ASTVal::Null | ASTVal::String(..) | ASTVal::Job(..) | ASTVal::Table(..) => format!("{}", self),
       ASTVal::Apply(lo, f, ro) => {
         let (lf, rf) = (
           match lo {
@@ -92,17 +92,20 @@ impl ASTVal {
         );
         do_tree(f.to_tree(), lf, rf)
       },
-      ASTVal::Assign(n, v) => {
-        format!("{}{}\n{}", n, CHARSET::Assign, v.to_tree())
+      ASTVal::Assign(n, v, outer) => {
+        format!("{}{}{}\n{}", if *outer { "outer " } else { "" }, n, CHARSET::Assign, v.to_tree())
       },
-      ASTVal::AliasAssign(n, v) => {
-        format!("{0}{1}{1}\n{2}", n, CHARSET::Assign, v.to_tree())
+      ASTVal::AliasAssign(n, v, outer) => {
+        format!("{}{1}{2}{2}\n{3}", if *outer { "outer " } else { "" }, n, CHARSET::Assign, v.to_tree())
       },
+      ASTVal::Block(vs) => vs.iter().map(|v| v.to_tree()).collect::<Vec<String>>().join("\n\n"),
       ASTVal::Operator(f, o, v) => do_tree(o.to_string(), f.to_tree(), v.to_tree()),
+      ASTVal::Lambda(l, r, body, _) => do_onetree(format!("fn {}", l.iter().chain(Some(r)).cloned().collect::<Vec<String>>().join(" ")), body.to_tree()),
       ASTVal::Command(name, args, stdin, _redir) => {
         do_tree(name.to_string(), ASTVal::Array(args.to_vec()).to_tree(), stdin.to_tree())
       },
       ASTVal::SymbolList(ss) => format!("-{}", ss.join("")),
+      ASTVal::Dict(_) => literal(self),
       ASTVal::Array(vs) => {
         if vs.len() == 0 {
           "()".to_string()
@@ -136,13 +139,134 @@ fn truncate_no_dotdotdot(s: String, l: usize) -> String {
   }
 }
 
+// box a rendered array or dict, its label set into the top left corner
+fn frame(label: String, inner: String) -> String {
+  let inner = inner.split("\n").enumerate().map(|(i, l)| if i == inner.matches("\n").count() { truncate_and_dotdotdot(l.to_string(), term::size::<usize>().0.saturating_sub(3 + 2)) } else { truncate_no_dotdotdot(l.to_string(), term::size::<usize>().0.saturating_sub(3 + 2)) } ).collect::<Vec<String>>().join("\n");
+
+  let depthchars = label.width();
+  
+  let (maxwidth, maxheight) = (
+    inner.split("\n").map(|l| l.width()).max().unwrap_or(0),
+    inner.split("\n").count()
+  );
+
+  let extracharsfromdepth = if depthchars > maxwidth { depthchars - maxwidth } else { 0 };
+  let maxwidth = std::cmp::max(maxwidth, depthchars);
+  
+  format!("┌{2}{0}┐\n{1}\n└{3}{0}┘", 
+    "─".repeat(maxwidth - depthchars), 
+    stitch(
+      Vec::from([
+        vec!["│"; maxheight].join("\n"), 
+        inner, 
+        vec![format!("{}│", " ".repeat(extracharsfromdepth)); maxheight].join("\n")
+      ]), ""),
+    label,
+    "─".repeat(depthchars)
+  )
+}
+
+// a value on one line, as it sits in a table's cell
+fn cell(v: &ASTVal) -> String {
+  match v {
+    ASTVal::String(s) => s.replace("\n", "\\n").replace("\t", "\\t"),
+    ASTVal::Null => String::new(),
+    ASTVal::Array(vs) => format!("({})", vs.iter().map(|v| cell(&v.variant)).collect::<Vec<String>>().join(" ")),
+    ASTVal::Dict(_) => literal(v),
+    ASTVal::Table(cs, rs) => format!("[table {}×{}]", rs.len(), cs.len()),
+    _ => format!("{}", v).replace("\n", " ").replace("\r", "")
+  }
+}
+
+fn quote(s: &str) -> String {
+  format!("\"{}\"", s.replace("\\", "\\\\").replace("\"", "\\\"").replace("\n", "\\n").replace("\t", "\\t"))
+}
+
+// a dict as it is written, so that it reads back the same wherever its values can be written too
+fn literal(v: &ASTVal) -> String {
+  match v {
+    ASTVal::String(s) => quote(s),
+    ASTVal::Array(vs) => format!("({})", vs.iter().map(|v| literal(&v.variant)).collect::<Vec<String>>().join(" ")),
+    ASTVal::Dict(es) if es.is_empty() => "{:}".to_string(),
+    ASTVal::Dict(es) => format!("{{{}}}", es.iter().map(|(k, v)| {
+      let bare = k.starts_with(|c: char| c.is_alphabetic() || c == '_') && k.chars().all(|c| c.is_alphanumeric() || c == '_');
+      format!("{}: {}", if bare { k.to_string() } else { quote(k) }, literal(&v.variant))
+    }).collect::<Vec<String>>().join(", ")),
+    ASTVal::Num(_) | ASTVal::Ident(_) | ASTVal::Symbol(_) => format!("{}", v),
+    _ => v.to_tree().replace("\n", " ")
+  }
+}
+
+// cut to a width, marking where it was cut
+fn fit(s: &str, width: usize) -> String {
+  if s.width() <= width {
+    return s.to_string();
+  }
+
+  let mut out = String::new();
+  for g in s.graphemes(true) {
+    if out.width() + g.width() + 1 > width {
+      break;
+    }
+    out += g;
+  }
+  out + "…"
+}
+
+// the columns aligned under their names, numbers to the right.  the widest columns are narrowed until
+// the grid fits the terminal, and rows which would not fit on the screen are left off
+fn grid(cols: &[String], rows: &[Vec<AST>]) -> String {
+  let (tcols, tlines) = term::size::<usize>();
+  let shown = if rows.len() + 6 > tlines { tlines.saturating_sub(8).max(3).min(rows.len()) } else { rows.len() };
+
+  let header = cols.iter().map(|c| cell(&ASTVal::String(c.to_string()))).collect::<Vec<String>>();
+  let cells = rows[..shown].iter().map(|r| r.iter().map(|v| cell(&v.variant)).collect::<Vec<String>>()).collect::<Vec<Vec<String>>>();
+
+  let mut widths = header.iter().map(|h| h.width()).collect::<Vec<usize>>();
+  for r in &cells {
+    for (w, c) in widths.iter_mut().zip(r) {
+      *w = std::cmp::max(*w, c.width());
+    }
+  }
+
+  // the frame takes five columns, and each divider three
+  let room = tcols.saturating_sub(3 + 2 + 3 * widths.len().saturating_sub(1));
+  while widths.iter().sum::<usize>() > room {
+    let widest = (0..widths.len()).max_by_key(|i| widths[*i]).unwrap();
+    if widths[widest] <= 3 {
+      break;
+    }
+    widths[widest] -= 1;
+  }
+
+  let line = |r: &Vec<String>, right: &dyn Fn(usize) -> bool| r.iter().enumerate().map(|(i, c)| {
+    let c = fit(c, widths[i]);
+    let pad = " ".repeat(widths[i] - c.width());
+    if right(i) { pad + &c } else { c + &pad }
+  }).collect::<Vec<String>>().join(" │ ");
+
+  let mut out = Vec::from([
+    line(&header, &|_| false),
+    widths.iter().map(|w| "─".repeat(*w)).collect::<Vec<String>>().join("─┼─")
+  ]);
+  for (r, vs) in cells.iter().zip(rows) {
+    out.push(line(r, &|i| matches!(vs[i].variant, ASTVal::Num(_))));
+  }
+  if shown < rows.len() {
+    out.push(format!("… {} more rows", rows.len() - shown));
+  }
+  out.join("\n")
+}
+
 impl fmt::Display for ASTVal {
   fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
     match self {
       ASTVal::Command(..) => write!(f, "[PIPE]"),
-      ASTVal::Assign(s, v) => write!(f, "{}{}\n\r{}", s, CHARSET::Assign, v.to_tree()),
-      ASTVal::AliasAssign(s, v) => write!(f, "{0}{1}{1}\n\r{2}", s, CHARSET::Assign, v.to_tree()),
+      ASTVal::Job(id) => write!(f, "[JOB {}]", id),
+      ASTVal::Assign(..) | ASTVal::AliasAssign(..) | ASTVal::Block(..) => write!(f, "{}", self.to_tree().replace("\n", "\n\r")),
       ASTVal::Num(i) => write!(f, "{}", i),
+      ASTVal::Bool(b) => write!(f, "{}", b),
+      ASTVal::Null => write!(f, "null"),
       ASTVal::String(s) => {
         let s = s.replace("\t", "  ");
         let s = match s.split("\n").map(|l| truncate_and_dotdotdot(l.to_string(), term::size::<usize>().0.saturating_sub(3 + 4) )).collect::<Vec<String>>() {
@@ -189,33 +313,20 @@ impl fmt::Display for ASTVal {
         while inner.len() > 0 && inner.last().unwrap().len() == 0 {
           inner.pop();
         }
-        let inner = inner.join("\n");
-        let inner = inner.split("\n").enumerate().map(|(i, l)| if i == inner.matches("\n").count() { truncate_and_dotdotdot(l.to_string(), term::size::<usize>().0.saturating_sub(3 + 2)) } else { truncate_no_dotdotdot(l.to_string(), term::size::<usize>().0.saturating_sub(3 + 2)) } ).collect::<Vec<String>>().join("\n");
 
-        let arrdepth = format!("{}", fathometer(&AST { variant: ASTVal::Array(vs.to_vec()), location: Location { col: 0, len: 0 } }));
-        let depthchars = arrdepth.width();
-        
-        let (maxwidth, maxheight) = (
-          inner.split("\n").map(|l| l.width()).max().unwrap_or(0),
-          inner.split("\n").count()
-        );
+        let arrdepth = format!("{}", fathometer(&AST { variant: ASTVal::Array(vs.to_vec()), location: Location { col: 0, len: 0, line: 0 } }));
+        write!(f, "{}", frame(arrdepth, inner.join("\n")))
+      },
+      // a column of keys beside their values
+      ASTVal::Dict(es) => {
+        let keys = es.iter().map(|(k, _)| k.replace("\n", "\\n").replace("\t", "\\t")).collect::<Vec<String>>();
+        let keywidth = keys.iter().map(|k| k.width()).max().unwrap_or(0);
+        let inner = keys.iter().zip(es).map(|(k, (_, v))| stitch(Vec::from([format!("{}{}", k, " ".repeat(keywidth - k.width())), format!("{}", v)]), " ")).collect::<Vec<String>>();
 
-        let extracharsfromdepth = if depthchars > maxwidth { depthchars - maxwidth } else { 0 };
-        let maxwidth = std::cmp::max(maxwidth, depthchars);
-        
-        write!(f, "┌{2}{0}┐\n{1}\n└{3}{0}┘", 
-          "─".repeat(maxwidth - depthchars), 
-          stitch(
-            Vec::from([
-              vec!["│"; maxheight].join("\n"), 
-              inner, 
-              vec![format!("{}│", " ".repeat(extracharsfromdepth)); maxheight].join("\n")
-            ]), ""),
-          arrdepth,
-          "─".repeat(depthchars)
-        )
+        write!(f, "{}", frame("dict".to_string(), inner.join("\n")))
       },
-      ASTVal::Operator(..) => write!(f, "{}", self.to_tree()),
+      ASTVal::Table(cs, rs) => write!(f, "{}", frame("table".to_string(), grid(cs, rs))),
+      ASTVal::Operator(..) | ASTVal::Lambda(..) => write!(f, "{}", self.to_tree()),
     }
   }
 }
@@ -270,7 +381,29 @@ pub fn highlight(s: &String) -> String {
              || textbuffer == "exit"
              || textbuffer == "list"
              || textbuffer == "json"
-             || textbuffer == "csv" {
+             || textbuffer == "csv"
+             || textbuffer == "bg"
+             || textbuffer == "fg"
+             || textbuffer == "wait"
+             || textbuffer == "jobs"
+             || textbuffer == "try"
+             || textbuffer == "if"
+             || textbuffer == "else"
+             || textbuffer == "getenv"
+             || textbuffer == "export"
+             || textbuffer == "unset"
+             || textbuffer == "withenv"
+             || textbuffer == "glob"
+             || textbuffer == "pushd"
+             || textbuffer == "popd"
+             || textbuffer == "dirs"
+             || textbuffer == "dict"
+             || textbuffer == "keys"
+             || textbuffer == "values"
+             || textbuffer == "table"
+             || textbuffer == "files"
+             || textbuffer == "fn"
+             || textbuffer == "outer" {
         out.insert_str(out.len() - textbuffer.len(), charset::Colors::BuiltinName.to_string().as_str());
         was_color = true;
       }
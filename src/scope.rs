@@ -0,0 +1,124 @@
+use crate::parse::AST;
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+type Scope = Arc<Mutex<HashMap<String, AST>>>;
+
+// innermost scope last: the global scope, then a script's, then one per function call.
+// scopes are shared, so a function keeps the ones it was written in and sees later writes to them
+#[derive(Clone)]
+pub struct Env {
+  scopes: Vec<Scope>,
+  // how many of the outermost scopes belong to the session, rather than to a function call
+  session: usize
+}
+
+impl std::fmt::Debug for Env {
+  // a function's scope may hold the function itself
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(f, "Env({} scopes)", self.scopes.len())
+  }
+}
+
+fn lock(s: &Scope) -> std::sync::MutexGuard<'_, HashMap<String, AST>> {
+  s.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+impl Env {
+  pub fn new() -> Env {
+    Env { scopes: vec![Scope::default()], session: 1 }
+  }
+
+  // the same scopes with a new one inside them, leaving this env as it was
+  pub fn child(&self) -> Env {
+    let mut scopes = self.scopes.clone();
+    scopes.push(Scope::default());
+    Env { scopes, session: self.session }
+  }
+
+  // a child which lasts as long as the session, as a script's scope does
+  pub fn session_child(&self) -> Env {
+    Env { session: self.scopes.len() + 1, ..self.child() }
+  }
+
+  // what a function written here keeps: only the scopes of the calls around it.
+  // the session's scopes hold the function itself, so keeping them too would keep both alive forever
+  pub fn capture(&self) -> Env {
+    Env { scopes: self.scopes[self.session..].to_vec(), session: self.session }
+  }
+
+  // the scope of a call to a function which captured this env, inside the session's scopes as the caller has them
+  pub fn call(&self, caller: &Env) -> Env {
+    let mut scopes = caller.scopes.iter().take(self.session).cloned().collect::<Vec<Scope>>();
+    scopes.extend(self.scopes.iter().cloned());
+    scopes.push(Scope::default());
+    Env { scopes, session: self.session }
+  }
+
+  pub fn get(&self, name: &str) -> Option<AST> {
+    self.scopes.iter().rev().find_map(|s| lock(s).get(name).cloned())
+  }
+
+  // shadows any outer binding of the same name
+  pub fn insert(&mut self, name: String, v: AST) {
+    lock(self.scopes.last().unwrap()).insert(name, v);
+  }
+
+  // writes to the nearest enclosing scope which already has the name, or else to the global scope
+  pub fn insert_outer(&mut self, name: String, v: AST) {
+    let inner = self.scopes.len() - 1;
+    let i = self.scopes[..inner].iter().rposition(|s| lock(s).contains_key(&name)).unwrap_or(0);
+    lock(&self.scopes[i]).insert(name, v);
+  }
+
+  // from the innermost scope only
+  pub fn remove(&mut self, name: &str) {
+    lock(self.scopes.last().unwrap()).remove(name);
+  }
+
+  pub fn keys(&self) -> Vec<String> {
+    self.scopes.iter().flat_map(|s| lock(s).keys().cloned().collect::<Vec<String>>()).collect()
+  }
+
+  pub fn clear(&mut self) {
+    *self = Env::new();
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{eval, parse, token};
+
+  fn run(env: &mut Env, line: &str) {
+    let s = line.to_string();
+    let mut toks = token::tokenize(&s).unwrap();
+    let cmd = parse::parse_commands(&mut toks, env, &s).unwrap();
+    let cmd = cmd.map(|c| eval::resolve(c, env, &s)).transpose().unwrap();
+    eval::eval_commands(cmd.into_iter().collect(), &s, env, false).unwrap();
+  }
+
+  #[test]
+  fn a_function_does_not_keep_the_scope_holding_it() {
+    let mut env = Env::new().session_child();
+    run(&mut env, "fact; fn n ((n < 1) if (1 else (n * (fact (n - 1)))))");
+    run(&mut env, "sq; fn n (n * n)");
+    run(&mut env, "sq 3");
+    assert_eq!(Arc::strong_count(&env.scopes[0]), 1);
+    assert_eq!(Arc::strong_count(&env.scopes[1]), 1);
+  }
+
+  #[test]
+  fn a_returned_function_keeps_the_call_it_was_written_in() {
+    let mut env = Env::new();
+    run(&mut env, "adder; fn n (fn m (n + m))");
+    run(&mut env, "add3; adder 3");
+    let s = "add3 4".to_string();
+    let mut toks = token::tokenize(&s).unwrap();
+    let cmd = parse::parse_commands(&mut toks, &env, &s).unwrap();
+    let cmd = cmd.map(|c| eval::resolve(c, &env, &s)).transpose().unwrap();
+    let v = eval::eval_commands(cmd.into_iter().collect(), &s, &mut env, false).unwrap();
+    assert_eq!(v.to_string(), "7");
+  }
+}
@@ -0,0 +1,131 @@
+use crate::jobs;
+
+use std::cell::Cell;
+use std::io;
+use std::os::unix::process::{CommandExt, ExitStatusExt};
+use std::sync::atomic::{AtomicBool, AtomicI32, Ordering};
+
+// process group of the pipeline holding the terminal, or 0 while the shell does
+static FOREGROUND: AtomicI32 = AtomicI32::new(0);
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+// whether the shell was in the foreground of its terminal, and so may hand it out.
+// one started in the background leaves the terminal to whoever holds it
+static OWNS_TERMINAL: AtomicBool = AtomicBool::new(false);
+
+thread_local! {
+  // every stage of a pipeline joins the group of its first spawned stage
+  static PIPELINE_GROUP: Cell<Option<i32>> = const { Cell::new(None) };
+}
+
+extern "C" fn on_sigint(_: libc::c_int) {
+  INTERRUPTED.store(true, Ordering::SeqCst);
+  let group = FOREGROUND.load(Ordering::SeqCst);
+  if group > 0 {
+    unsafe { libc::kill(-group, libc::SIGINT); }
+  }
+}
+
+pub fn install() {
+  unsafe {
+    libc::signal(libc::SIGINT, on_sigint as extern "C" fn(libc::c_int) as libc::sighandler_t);
+    // handing the terminal back from a background group would otherwise stop the shell
+    libc::signal(libc::SIGTTOU, libc::SIG_IGN);
+  }
+  OWNS_TERMINAL.store(in_foreground(), Ordering::SeqCst);
+}
+
+fn in_foreground() -> bool {
+  unsafe { libc::isatty(0) == 1 && libc::tcgetpgrp(0) == libc::getpgrp() }
+}
+
+// an interactive shell stops until it is brought to the foreground, as the terminal is needed to read lines
+pub fn wait_for_foreground() {
+  while unsafe { libc::isatty(0) == 1 } && !in_foreground() {
+    unsafe { libc::kill(0, libc::SIGTTIN); }
+  }
+  OWNS_TERMINAL.store(in_foreground(), Ordering::SeqCst);
+}
+
+pub fn take_interrupt() -> bool {
+  INTERRUPTED.swap(false, Ordering::SeqCst)
+}
+
+fn has_terminal() -> bool {
+  OWNS_TERMINAL.load(Ordering::SeqCst)
+}
+
+pub fn give_terminal(group: i32) {
+  FOREGROUND.store(group, Ordering::SeqCst);
+  if has_terminal() {
+    unsafe { libc::tcsetpgrp(0, if group > 0 { group } else { libc::getpgrp() }); }
+  }
+}
+
+pub fn begin_pipeline() -> Option<i32> {
+  PIPELINE_GROUP.with(|g| g.replace(None))
+}
+
+// commands run as arguments form pipelines of their own, after which the outer one gets the terminal back
+pub fn end_pipeline(outer: Option<i32>) {
+  PIPELINE_GROUP.with(|g| g.set(outer));
+  if !jobs::in_job() {
+    give_terminal(outer.unwrap_or(0));
+  }
+}
+
+pub fn setup_group(cmd: &mut std::process::Command) {
+  let group = PIPELINE_GROUP.with(|g| g.get());
+  let takes_terminal = group.is_none() && !jobs::in_job() && has_terminal();
+
+  cmd.process_group(group.unwrap_or(0));
+  unsafe {
+    cmd.pre_exec(move || {
+      // the child races the shell to claim the terminal, or an early read would stop it
+      if takes_terminal {
+        libc::tcsetpgrp(0, libc::getpgrp());
+      }
+      libc::signal(libc::SIGTTOU, libc::SIG_DFL);
+      Ok(())
+    });
+  }
+}
+
+pub fn joined(pid: u32) {
+  if PIPELINE_GROUP.with(|g| g.get()).is_none() {
+    PIPELINE_GROUP.with(|g| g.set(Some(pid as i32)));
+    // a job brought to the foreground keeps the terminal from one pipeline to the next
+    if !jobs::in_job() || jobs::set_group(pid as i32) {
+      give_terminal(pid as i32);
+    }
+  }
+}
+
+pub fn wait(ch: &mut std::process::Child) -> io::Result<std::process::ExitStatus> {
+  let pid = ch.id() as libc::pid_t;
+  loop {
+    let mut status = 0;
+    if unsafe { libc::waitpid(pid, &mut status, libc::WUNTRACED) } < 0 {
+      let e = io::Error::last_os_error();
+      if e.kind() == io::ErrorKind::Interrupted { continue; }
+      return Err(e);
+    }
+
+    if libc::WIFSTOPPED(status) {
+      if jobs::in_job() {
+        jobs::stopped();
+      } else {
+        // a half-run pipeline cannot move to the job table, so a stop only resumes it
+        unsafe { libc::kill(-libc::getpgid(pid), libc::SIGCONT); }
+      }
+      continue;
+    }
+
+    if jobs::in_job() {
+      jobs::reaped(ch.id());
+    } else if libc::WIFSIGNALED(status) && libc::WTERMSIG(status) == libc::SIGINT {
+      INTERRUPTED.store(true, Ordering::SeqCst);
+    }
+
+    return Ok(std::process::ExitStatus::from_raw(status));
+  }
+}
@@ -1,4 +1,5 @@
 extern crate os_pipe;
+extern crate libc;
 
 extern crate termion;
 use termion::raw::IntoRawMode;
@@ -10,26 +11,49 @@ use unicode_segmentation::UnicodeSegmentation;
 
 use std::io::{self, Write};
 use std::env;
-use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 
+mod bigint;
 mod charset;
+mod complete;
 use charset::{Colors, CHARSET};
 
+mod dirs;
 mod editor;
 
 mod error;
+use error::{Error, ErrorKind, noteformat};
 mod eval;
+mod files;
+mod glob;
+mod history;
+mod jobs;
+mod number;
 
 mod parse;
 use parse::{AST, ASTVal, Location};
 
 mod pretty;
+mod scope;
+use scope::Env;
+mod signals;
 mod term;
 mod token;
 
 const VERSTR: &str = "v0.1";
 
-fn eval_pipeline(command: String, environment: &mut HashMap<String, AST>, allow_command: bool) -> Result<AST, String> {
+const ENV_COMMANDS: [&str; 8] = ["rtf", "cm", "c", "clear", "wipe", "info", "help", "source"];
+
+const RC_FILE: &str = ".brierc";
+
+// xc_pos counts chars, but String ranges are in bytes
+fn replace_chars(s: &mut String, start: usize, end: usize, with: &str) {
+  let byte = |i: usize| s.char_indices().nth(i).map(|(b, _)| b).unwrap_or(s.len());
+  let (start, end) = (byte(start), byte(end));
+  s.replace_range(start..end, with);
+}
+
+fn eval_pipeline(command: String, environment: &mut Env, allow_command: bool) -> Result<AST, Error> {
   let toks = token::tokenize(&command);
         
   let commands = toks.and_then(|mut ts| parse::parse_commands(&mut ts, &environment, &command));
@@ -46,43 +70,131 @@ fn eval_pipeline(command: String, environment: &mut HashMap<String, AST>, allow_
   evald
 }
 
+fn rc_path() -> Option<PathBuf> {
+  env::var_os("HOME").map(|h| PathBuf::from(h).join(RC_FILE))
+}
+
+// under --errexit a process which fails ends the script, as an error on its statement
+fn check_status(statement: &String) -> Result<(), Error> {
+  match eval::last_status() {
+    0 => Ok(()),
+    n => Err(error::error(ErrorKind::Process, statement, Location { col: 0, len: statement.lines().next().unwrap_or("").chars().count(), line: 0 }, format!("Command failed with status {}.", n), "The script was run with --errexit, which stops at the first failing command.  Catch the failure with try or test it with if to go on."))
+  }
+}
+
+// Ctrl-C ends the script after the statement it interrupted, as an error on that statement
+fn check_interrupt(statement: &String) -> Result<(), Error> {
+  if signals::take_interrupt() {
+    Err(error::error(ErrorKind::Interrupt, statement, Location { col: 0, len: statement.lines().next().unwrap_or("").chars().count(), line: 0 }, "Interrupted.", "The script was stopped with Ctrl-C, and the rest of it skipped."))
+  } else {
+    Ok(())
+  }
+}
+
+// evaluate a file statement by statement, stopping at the first failure
+fn source_file<P: AsRef<Path>>(filename: P, environment: &mut Env, errexit: bool) -> Result<(), Error> {
+  let filename = filename.as_ref();
+  let str = std::fs::read_to_string(filename).map_err(|e| Error::new(ErrorKind::Io, format!("Failed to open file `{}`", filename.display()), noteformat!("Trace:\n\r{}", e)))?;
+
+  // the statement read so far, and the line it started on
+  let mut pending: Option<(String, usize)> = None;
+  let mut lines = str.split("\n").enumerate().peekable();
+
+  while let Some((i, line)) = lines.next() {
+    let (statement, start) = match pending.take() {
+      Some((p, start)) => (format!("{}\n{}", p, line), start),
+      None => (line.to_string(), i)
+    };
+    // an unfinished statement at the end of the file is run anyway, to report what is missing
+    if token::needs_continuation(&statement) && lines.peek().is_some() {
+      pending = Some((statement, start));
+      continue;
+    }
+
+    // errors raised while evaluating are headed with the file and line
+    let outer = error::set_source(Some((filename.display().to_string(), start)));
+    let res = eval_pipeline(statement.clone(), environment, false)
+      .and_then(|_| if errexit { check_status(&statement) } else { Ok(()) })
+      .and_then(|_| check_interrupt(&statement));
+    error::set_source(outer);
+    res?;
+
+  }
+
+  Ok(())
+}
+
+fn source_rc(environment: &mut Env) {
+  if let Some(rc) = rc_path().filter(|p| p.exists()) {
+    if let Err(e) = source_file(&rc, environment, false) {
+      println!("{}", e);
+    }
+  }
+}
+
 fn main() {
+  signals::install();
+
+  // the shell runs on a thread of its own for the sake of a deeper stack
+  let shell = std::thread::Builder::new().stack_size(eval::STACK_SIZE).spawn(shell).unwrap();
+  if shell.join().is_err() {
+    std::process::exit(101);
+  }
+}
+
+fn shell() {
   let mut args: Vec<String> = env::args().collect();
   if args.len() > 1 {
     args.remove(0);
-    let filename = args.remove(0);
+    let mut filename = args.remove(0);
+    let (mut use_rc, mut json_errors, mut errexit) = (false, false, false);
+    while !args.is_empty() && (filename == "--rc" || filename == "--json-errors" || filename == "--errexit") {
+      use_rc |= filename == "--rc";
+      json_errors |= filename == "--json-errors";
+      errexit |= filename == "--errexit";
+      filename = args.remove(0);
+    }
     if args.len() > 0 {
       println!("\x1b[31mERR:\x1b[0m Unexpected extra argument `{}`", args.remove(0));
       std::process::exit(1);
     }
 
-    match std::fs::read_to_string(&filename) {
-      Ok(str) => {
-        let mut environment = HashMap::new();
-        let l = Location { col: 0, len: 0};
-        environment.insert("ARGV".to_string(), AST { variant: ASTVal::Array(args.into_iter().map(|x| AST { variant: ASTVal::String(x), location: l }).collect()), location: l });
-        for line in str.split("\n") {
-          let result = eval_pipeline(line.to_string(), &mut environment, false);
-          if let Err(e) = result {
-            println!("{}", e);
-            break;
-          }
-        }
-      },
-      Err(_e) => { println!("\x1b[31mERR:\x1b[0m Failed to open file `{}`", filename); }
+    let mut environment = Env::new();
+    let l = Location { col: 0, len: 0, line: 0 };
+    environment.insert("ARGV".to_string(), AST { variant: ASTVal::Array(args.into_iter().map(|x| AST { variant: ASTVal::String(x), location: l }).collect()), location: l });
+
+    if use_rc {
+      source_rc(&mut environment);
+    }
+    // the script's names sit above the rc file's, which `outer` can still reach
+    let mut environment = environment.session_child();
+    if let Err(e) = source_file(&filename, &mut environment, errexit) {
+      // errors go to stderr, colored only for a terminal
+      if json_errors {
+        eprintln!("{}", e.to_json().dump());
+      } else {
+        eprintln!("{}", e.render(unsafe { libc::isatty(2) == 1 }));
+      }
+      // as a shell reports a process ended by SIGINT
+      std::process::exit(if e.kind == ErrorKind::Interrupt { 130 } else { 1 });
     }
     
     return;
   }
   
+  signals::wait_for_foreground();
   println!("Brie Shell, {}.  `)help` for manual.", VERSTR);
   
   let prompt = "$";
-  let mut environment = HashMap::new();
-  let mut history = Vec::new();
+  let mut environment = Env::new();
+  source_rc(&mut environment);
+  let mut history = history::load();
 
   let mut replablehistory = Vec::new();
   let mut commit_every = true;
+
+  // earlier lines of a statement left open by a parenthesis or a trailing backslash
+  let mut pending: Option<String> = None;
   
   let mut stdout = match io::stdout().into_raw_mode().map_err(|e| format!("Failed to enter raw mode.  Trace:\n\r{}", e)) {
     Ok(o) => o,
@@ -108,12 +220,25 @@ fn main() {
 
     let pathlen;
 
+    for notice in jobs::notices() {
+      write!(stdout, "\x1b[33m{}\x1b[0m\n\r", notice).unwrap();
+    }
+
     // "0,0"
     write!(stdout, "{}", termion::cursor::Show).unwrap();
     stdout.flush().unwrap();
     
-    if let Ok(path) = env::current_dir() { 
-      let sfmt = format!("{1}{0} ", prompt, path.display());
+    if pending.is_some() {
+      let sfmt = "> ";
+      pathlen = sfmt.len();
+      write!(stdout, "{1}{}{2}", sfmt, termion::color::Fg(termion::color::LightRed), Colors::Reset).unwrap();
+      stdout.flush().unwrap();
+    } else if let Ok(path) = env::current_dir() { 
+      // the last line's failure shows until the next line runs
+      let sfmt = match eval::last_status() {
+        0 => format!("{1}{0} ", prompt, path.display()),
+        n => format!("[{2}] {1}{0} ", prompt, path.display(), n)
+      };
       pathlen = sfmt.len();
       write!(stdout, "{1}{}{2}", sfmt, termion::color::Fg(termion::color::LightRed), Colors::Reset).unwrap();
       stdout.flush().unwrap();
@@ -124,6 +249,12 @@ fn main() {
     }
     
     let mut xc_pos: usize = 0;
+
+    // query and index of the current match, while searching with Ctrl-R/Ctrl-S
+    let mut search: Option<(String, usize)> = None;
+
+    // candidates, the one last chosen, and where the completed word starts, while cycling with Tab
+    let mut completions: Option<(Vec<String>, usize, usize)> = None;
     
     for c in stdin.keys() {
       let (term_cols, _term_rows) = term::size::<u16>();
@@ -137,12 +268,93 @@ fn main() {
         Ok(o) => o
       };
 
+      // anything but typing and stepping through matches leaves the search, keeping the matched line
+      if !matches!(key, Key::Char(c) if c != '\t') && !matches!(key, Key::Backspace | Key::Ctrl('r') | Key::Ctrl('s')) {
+        search = None;
+      }
+      if key != Key::Char('\t') {
+        completions = None;
+      }
+
       let mut is_final = false;
       match key {
         Key::Char('\n') => {
+          search = None;
           is_final = true;
         },
+        Key::Char('\t') => {
+          match completions {
+            Some((ref cands, ref mut chosen, start)) => {
+              *chosen = (*chosen + 1) % cands.len();
+              replace_chars(command, start, xc_pos, &cands[*chosen]);
+              xc_pos = start + cands[*chosen].chars().count();
+            },
+            None => {
+              let c = complete::complete(command, xc_pos, &environment, &ENV_COMMANDS);
+              let with = match c.candidates.len() {
+                0 => None,
+                1 if c.quoted && !c.candidates[0].ends_with('/') => Some(c.candidates[0].clone() + "\""),
+                1 => Some(c.candidates[0].clone()),
+                n => {
+                  let prefix = complete::common_prefix(&c.candidates);
+                  completions = Some((c.candidates, n - 1, c.start));
+                  Some(prefix)
+                }
+              };
+              if let Some(with) = with {
+                replace_chars(command, c.start, xc_pos, &with);
+                xc_pos = c.start + with.chars().count();
+              }
+            }
+          }
+        },
+        Key::Ctrl('r') | Key::Ctrl('s') => {
+          let backward = key == Key::Ctrl('r');
+          match search {
+            None => { search = Some((String::new(), history_len - 1)); },
+            Some((ref q, ref mut i)) => {
+              let from = if backward { i.checked_sub(1) } else { Some(*i + 1) };
+              if let Some(found) = from.and_then(|from| history::find(&immut_history[..history_len - 1], q, from, backward)) {
+                *i = found;
+                history_index = found;
+                *command = immut_history[found].clone();
+                xc_pos = command.len();
+              }
+            }
+          }
+        },
+        Key::Char(x) if search.is_some() => {
+          let (q, i) = search.as_mut().unwrap();
+          q.push(x);
+          if let Some(found) = history::find(&immut_history[..history_len - 1], q, *i, true) {
+            *i = found;
+            history_index = found;
+            *command = immut_history[found].clone();
+            xc_pos = command.len();
+          }
+        },
+        Key::Backspace if search.is_some() => {
+          let (q, i) = search.as_mut().unwrap();
+          q.pop();
+          if let Some(found) = history::find(&immut_history[..history_len - 1], q, history_len - 1, true) {
+            *i = found;
+            history_index = found;
+            *command = immut_history[found].clone();
+            xc_pos = command.len();
+          }
+        },
+        Key::Ctrl('c') if pending.is_some() => {
+          // abandon the whole statement for a fresh prompt
+          pending = None;
+          history.pop();
+          write!(stdout, "\n\r").unwrap();
+          continue 'termloop;
+        },
         Key::Ctrl('c') => {
+          command.clear();
+          xc_pos = 0;
+        },
+        Key::Ctrl('d') if command.is_empty() => {
           write!(stdout, "\n\r").unwrap();
           std::process::exit(0);
         },
@@ -228,7 +440,24 @@ fn main() {
 
       let mut linesdown = 0;
 
-      if command.graphemes(true).nth(0).unwrap_or(" ") == CHARSET::EnvCommand {
+      if let Some((ref q, _)) = search {
+        print!("\x1b[33m(history search) `{}'\x1b[0m\n\r", q);
+        linesdown += 1;
+      }
+
+      if let Some((ref cands, ..)) = completions {
+        let menu = cands.iter().map(|c| c.rsplit('/').find(|p| !p.is_empty()).unwrap_or(c)).collect::<Vec<&str>>().join("  ");
+        let menu = if menu.chars().count() > term_cols as usize { menu.chars().take((term_cols as usize).saturating_sub(3)).collect::<String>() + "..." } else { menu };
+        print!("\x1b[36m{}\x1b[0m\n\r", menu);
+        linesdown += 1;
+      }
+
+      let statement = match pending { Some(ref p) => format!("{}\n{}", p, command), None => command.clone() };
+      let continues = token::needs_continuation(&statement);
+
+      if is_final && continues {
+        // nothing runs until the statement is closed
+      } else if pending.is_none() && command.graphemes(true).nth(0).unwrap_or(" ") == CHARSET::EnvCommand {
         let c = command.chars().skip(1).collect::<String>();
         let mut cmd: Vec<String> = c.trim().split(" ").map(|x| x.to_lowercase()).collect();
 
@@ -248,6 +477,14 @@ fn main() {
                 }
               }
             },
+            "source" => {
+              // paths are case-sensitive, so take the argument before it was lowercased
+              let f = c.trim().split_once(' ').map(|(_, f)| PathBuf::from(f.trim())).or_else(rc_path);
+              match f {
+                None => Err(")source expects a filename to evaluate.".to_string()),
+                Some(f) => source_file(&f, &mut environment, false).map(|_| format!("Sourced {}.", f.display())).map_err(|e| e.to_string())
+              }
+            },
             "cm" => {
               commit_every = !commit_every;
               Ok(format!("Commit mode is now {}.", if commit_every { "automatic" } else { "manual" }))
@@ -274,7 +511,7 @@ fn main() {
             "info" => {
               let torun = cmd[1..].join(" ");
               let res = match token::tokenize(&torun).and_then(|mut c| parse::parse_commands(&mut c, &environment, &torun)) {
-                Err(e) => Err(e),
+                Err(e) => Err(e.to_string()),
                 Ok(o) => Ok(match o {
                   None => "".to_string(),
                   Some(s) => s.to_tree()
@@ -298,20 +535,21 @@ fn main() {
           }.unwrap();
         }
       } else {
-        let result = eval_pipeline(command.clone(), &mut environment, !is_final);
+        let result = eval_pipeline(statement.clone(), &mut environment, !is_final);
   
         match result {
           Err(e) => if is_final {
+            let e = e.to_string();
             linesdown += e.lines().count();
             write!(io::stdout(), "{}", e) 
           } else {
-            let towrite = if e.len() > 0 && e.split(" ").nth(0).unwrap() == error::error("", 0, 0, "", "").split(" ").nth(0).unwrap() { "[err]" } else { e.as_str() };
+            let towrite = if e.kind == ErrorKind::Preview { e.message.as_str() } else { "[err]" };
             linesdown += towrite.lines().count();
             write!(io::stdout(), "{}", towrite)
           },
           Ok(v) => {
-            if is_final && !command.trim().is_empty() && commit_every {
-              replablehistory.push(command.clone());
+            if is_final && !statement.trim().is_empty() && commit_every {
+              replablehistory.push(statement.clone());
             }
             let v = format!("{}", v);
             linesdown += v.lines().count();
@@ -337,7 +575,15 @@ fn main() {
         
         stdout.flush().unwrap();
       } else {
-        write!(stdout, "\n\r").unwrap();
+        if !continues {
+          write!(stdout, "\n\r").unwrap();
+        }
+        pending = continues.then_some(statement);
+        if history::commit(&mut history) {
+          if let Err(e) = history::save(&history) {
+            write!(stdout, "\x1b[31mERR:\x1b[0m {}\n\r", e).unwrap();
+          }
+        }
         continue 'termloop;
       }
     }
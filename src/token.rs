@@ -1,15 +1,53 @@
 
 use crate::charset::CHARSET;
-use crate::error::error;
+use crate::error::{error, Error, ErrorKind};
+use crate::parse::Location;
 use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
 
 #[derive(Debug, Clone)]
 pub struct Token {
   pub val: String,
   pub col: usize,
+  pub line: usize,
   pub followed: bool
 }
 
+// the opening brace of a dict literal, told apart from the left tack by what follows it
+pub const DICT_OPEN: &str = "{:";
+
+// named builtins, as opposed to the symbolic ones in CHARSET
+pub const BUILTINS: [&str; 28] = [
+  "cd",
+  "exit",
+  "num",
+  "collect",
+  "pipe",
+  "list",
+  "csv",
+  "json",
+  "bg",
+  "fg",
+  "wait",
+  "jobs",
+  "try",
+  "if",
+  "else",
+  "getenv",
+  "export",
+  "unset",
+  "withenv",
+  "glob",
+  "pushd",
+  "popd",
+  "dirs",
+  "dict",
+  "keys",
+  "values",
+  "table",
+  "files"
+];
+
 pub fn in_operators<S: AsRef<str>>(c: S) -> bool {
   let c = c.as_ref();
   
@@ -17,11 +55,7 @@ pub fn in_operators<S: AsRef<str>>(c: S) -> bool {
   || c == CHARSET::Minus
   || c == CHARSET::Times
   || c == CHARSET::Divide
-  || c == "cd" 
-  || c == "exit"
-  || c == "num"
-  || c == "collect"
-  || c == "pipe"
+  || BUILTINS.contains(&c)
   || c == CHARSET::LTack
   || c == CHARSET::RTack
   || c == CHARSET::Concat
@@ -36,9 +70,6 @@ pub fn in_operators<S: AsRef<str>>(c: S) -> bool {
   || c == CHARSET::Transpose
   || c == CHARSET::Take
   || c == CHARSET::Rotate
-  || c == "list"
-  || c == "csv"
-  || c == "json"
 }
 
 // poor naming...
@@ -54,11 +85,53 @@ pub fn in_true_operators<S: AsRef<str>>(c: S) -> bool {
   || c == CHARSET::Selfie
 }
 
-pub fn tokenize(c: &str) -> Result<Vec<Token>, String> {
+// 1e6 and 2.5e-3; an e without digits after it is left for the next token
+fn take_exponent<'a, I: Iterator<Item = (usize, &'a str)> + Clone>(iter: &mut std::iter::Peekable<I>, str: &mut String) {
+  let mut ahead = iter.clone();
+  let mut exp = match ahead.next() {
+    Some((_, e)) if e == "e" || e == "E" => e.to_string(),
+    _ => { return; }
+  };
+  if let Some((_, sign)) = ahead.next_if(|(_, c)| *c == "-" || *c == "+") {
+    exp += sign;
+  }
+  let mut any = false;
+  while let Some((_, d)) = ahead.next_if(|(_, d)| d.chars().all(|c| c.is_ascii_digit())) {
+    exp += d;
+    any = true;
+  }
+  if any {
+    *str += &exp;
+    *iter = ahead;
+  }
+}
+
+// { opens a dict literal when a key and a colon follow it directly, as in {name: or {"a key":, or when it is {:}
+fn opens_dict<'a, I: Iterator<Item = (usize, &'a str)> + Clone>(iter: &std::iter::Peekable<I>) -> bool {
+  let mut ahead = iter.clone().map(|(_, c)| c);
+  match ahead.next() {
+    Some(":") => ahead.next() == Some("}"),
+    Some("\"") => loop {
+      match ahead.next() {
+        Some("\\") => { ahead.next(); },
+        Some("\"") => break ahead.next() == Some(":"),
+        None | Some("\n") | Some("\r\n") => break false,
+        _ => {}
+      }
+    },
+    Some(k) if k.chars().all(|c| c.is_alphabetic()) || k == "_" => ahead.find(|c| !c.chars().all(|c| c.is_alphanumeric()) && *c != "_") == Some(":"),
+    _ => false
+  }
+}
+
+pub fn tokenize(c: &str) -> Result<Vec<Token>, Error> {
   let mut toks = Vec::new();
   let mut iter = c.graphemes(true).enumerate().peekable();
+  // columns are counted from the start of the current line
+  let (mut line, mut line_start) = (0, 0);
   
-  while let Some((col, char)) = iter.next() {
+  while let Some((abscol, char)) = iter.next() {
+    let col = abscol - line_start;
     if char.chars().all(|c| c.is_numeric()) {
       let mut str = String::new();
       str += char;
@@ -73,8 +146,9 @@ pub fn tokenize(c: &str) -> Result<Vec<Token>, String> {
           break;
         }
       }
+      take_exponent(&mut iter, &mut str);
 
-      toks.push(Token { val: str, col, followed: false });
+      toks.push(Token { val: str, col, line, followed: false });
     } else if char.chars().all(|c| c.is_alphabetic()) || char == "_" {
       let mut str = String::new();
       str += char;
@@ -85,7 +159,7 @@ pub fn tokenize(c: &str) -> Result<Vec<Token>, String> {
         iter.next();
       }
 
-      toks.push(Token { val: str, col, followed: false });
+      toks.push(Token { val: str, col, line, followed: false });
     } else if char == "\"" {
       let mut str = String::new();
       str += char;
@@ -94,13 +168,16 @@ pub fn tokenize(c: &str) -> Result<Vec<Token>, String> {
       
       loop {
         if let Some((col, nchar)) = iter.peek() {
-          lastcol = *col;
+          lastcol = *col - line_start;
           
           let s = *nchar;
+          if s == "\n" || s == "\r\n" {
+            break Err(error(ErrorKind::Syntax, c, Location { col: lastcol, len: 1, line }, "Unexpected newline!", "Strings cannot span lines.  Use \\n for a newline inside a string."));
+          }
           if s == "\\" {
             iter.next();
             if let Some((col, escchar)) = iter.peek() {
-              lastcol = *col;
+              lastcol = *col - line_start;
               match *escchar {
                 "n" => str.push('\n'),
                 "t" => str.push('\t'),
@@ -110,7 +187,7 @@ pub fn tokenize(c: &str) -> Result<Vec<Token>, String> {
               };
               iter.next();
             } else {
-              break Err(error(c, lastcol + 1, 1, "Unexpected EOF!", "While parsing a string escape code, EOF was reached."));
+              break Err(error(ErrorKind::Syntax, c, Location { col: lastcol + 1, len: 1, line }, "Unexpected EOF!", "While parsing a string escape code, EOF was reached."));
             }
             continue;
           }
@@ -120,11 +197,11 @@ pub fn tokenize(c: &str) -> Result<Vec<Token>, String> {
           if s == "\"" { break Ok(()); }
           str += s;
         } else {
-          break Err(error(c, lastcol + 1, 1, "Unexpected EOF!", "While parsing a string, EOF was reached.  Expect terminating quote."));
+          break Err(error(ErrorKind::Syntax, c, Location { col: lastcol + 1, len: 1, line }, "Unexpected EOF!", "While parsing a string, EOF was reached.  Expect terminating quote."));
         }
       }?;
 
-      toks.push(Token { val: str, col, followed: false });
+      toks.push(Token { val: str, col, line, followed: false });
     } else if char == "-" && iter.peek().and_then(|x| if x.1.chars().all(|c| c.is_numeric()) { Some(()) } else { None }).is_some() { 
       let mut str = String::new();
       str += char;
@@ -135,8 +212,9 @@ pub fn tokenize(c: &str) -> Result<Vec<Token>, String> {
         str += nchar;
         iter.next();
       }
+      take_exponent(&mut iter, &mut str);
 
-      toks.push(Token { val: str, col, followed: false });
+      toks.push(Token { val: str, col, line, followed: false });
     } else if char == "-" && iter.peek().and_then(|x| if x.1.chars().all(|c| c.is_alphabetic()) || x.1 == "-" { Some(()) } else { None }).is_some() {
       let mut str = String::new();
       str += char;
@@ -151,17 +229,29 @@ pub fn tokenize(c: &str) -> Result<Vec<Token>, String> {
       if str == "--" {
         // wasting memory is the product of laziness.  and yes, it's one char.
         let (dash, dashy) = (str.pop().unwrap().to_string(), str);
-        toks.push(Token { val: dash, col, followed: false });
-        toks.push(Token { val: dashy, col, followed: false });
+        toks.push(Token { val: dash, col, line, followed: false });
+        toks.push(Token { val: dashy, col, line, followed: false });
       } else {
-        toks.push(Token { val: str, col, followed: false });
+        toks.push(Token { val: str, col, line, followed: false });
       }
     } else if char == CHARSET::Assign && iter.peek().and_then(|(_, x)| (*x == CHARSET::Assign).then(|| ())).is_some() {
       iter.next();
       
-      toks.push(Token { val: CHARSET::Assign.as_str().repeat(2), col, followed: false });
+      toks.push(Token { val: CHARSET::Assign.as_str().repeat(2), col, line, followed: false });
     } else if char == CHARSET::Pipe && iter.peek().and_then(|(_, x)| (*x == CHARSET::Pipe).then(|| ())).is_some() {
-      break; // comment, exit
+      // comment, skip to the end of the line
+      while let Some((_, nchar)) = iter.peek() {
+        if *nchar == "\n" || *nchar == "\r\n" { break; }
+        iter.next();
+      }
+      continue;
+    } else if char == CHARSET::LTack && opens_dict(&iter) {
+      // the colon of {:} is part of the brace, leaving nothing before the close
+      if iter.peek().is_some_and(|(_, x)| *x == CHARSET::Take) {
+        iter.next();
+      }
+
+      toks.push(Token { val: DICT_OPEN.to_string(), col, line, followed: false });
     } else if   in_operators(char) 
       || char == "("
       || char == CHARSET::Assign
@@ -170,22 +260,24 @@ pub fn tokenize(c: &str) -> Result<Vec<Token>, String> {
       || char == CHARSET::EndOperator
       || in_true_operators(char) 
     {
-      toks.push(Token { val: char.to_string(), col, followed: false });
+      toks.push(Token { val: char.to_string(), col, line, followed: false });
     } else if char == ")" {
-      toks.push(Token { val: char.to_string(), col, followed: false });
+      toks.push(Token { val: char.to_string(), col, line, followed: false });
     } else if char == " " || char == "\t" {
       while let Some((_, nchar)) = iter.peek() {
         if *nchar != " " || *nchar != "\t" { break }
         iter.next();
       }
       continue;
-    } else if char == "\n" {
-      break;
+    } else if char == "\n" || char == "\r\n" {
+      line += 1;
+      line_start = abscol + 1;
+      continue;
     } else {
-      return Err(error(c, col, 1, "Unknown token", "This is a typo; this symbol does not exist."));
+      return Err(error(ErrorKind::Syntax, c, Location { col, len: 1, line }, "Unknown token", "This is a typo; this symbol does not exist."));
     }
 
-    if iter.peek().and_then(|(_, c)| (*c != ")" && *c != " " && *c != "\t").then(|| ())).is_some() {
+    if iter.peek().and_then(|(_, c)| (*c != ")" && *c != " " && *c != "\t" && *c != "\n" && *c != "\r\n").then_some(())).is_some() {
       let l = toks.len();
       toks[l - 1].followed = true;
     }
@@ -194,7 +286,37 @@ pub fn tokenize(c: &str) -> Result<Vec<Token>, String> {
   Ok(toks)
 }
 
+// whether a statement is still open, either by an unclosed parenthesis or a line ending in a pipe, which never ends a valid statement
+pub fn needs_continuation(statement: &str) -> bool {
+  let last = statement.lines().last().unwrap_or("");
+  if tokenize(last).is_ok_and(|toks| toks.last().is_some_and(|t| t.val == CHARSET::Pipe)) {
+    return true;
+  }
+
+  let mut depth = 0i32;
+  for line in statement.lines() {
+    let mut chars = line.chars().peekable();
+    let mut in_string = false;
+    while let Some(c) = chars.next() {
+      match c {
+        '\\' if in_string => { chars.next(); },
+        '"' => { in_string = !in_string; },
+        _ if in_string => {},
+        '(' => { depth += 1; },
+        ')' => { depth -= 1; },
+        '|' if chars.peek() == Some(&'|') => { break; },
+        _ => {}
+      }
+    }
+  }
+  depth > 0
+}
+
 impl Token {
+  pub fn location(&self) -> Location {
+    Location { col: self.col, len: self.val.width(), line: self.line }
+  }
+
   pub fn is_num(&self) -> bool {
       self.val.chars().nth(0).unwrap().is_digit(10)
     || (